@@ -1,6 +1,7 @@
 use std::time::Duration;
 use worker::{
-    console_log, durable_object, wasm_bindgen, DurableObject, Env, Request, Response, Result, State,
+    console_log, durable_object, wasm_bindgen, AlarmScheduler, DurableObject, Env, Request,
+    Response, Result, State, Timer,
 };
 
 use super::SomeSharedData;
@@ -45,3 +46,96 @@ pub async fn handle_alarm(_req: Request, env: Env, _data: SomeSharedData) -> Res
     // https://developers.cloudflare.com/workers/platform/compatibility-dates#durable-object-stubfetch-requires-a-full-url
     stub.fetch_with_str("https://fake-host/alarm").await
 }
+
+/// Multiplexes timers over its alarm with an [`AlarmScheduler`], recording in its KV storage
+/// which timers fired and how often the failing one was attempted.
+#[durable_object]
+pub struct TimerObject {
+    state: State,
+    timers: AlarmScheduler,
+}
+
+const TIMER_NAMES: [&str; 4] = ["early", "late", "flaky", "garbled"];
+
+impl DurableObject for TimerObject {
+    fn new(state: State, _: Env) -> Self {
+        let timers = AlarmScheduler::new(state.storage())
+            .expect("create timers table")
+            .with_max_attempts(2)
+            .with_retry_delay(Duration::from_millis(50));
+        Self { state, timers }
+    }
+
+    async fn fetch(&self, req: Request) -> Result<Response> {
+        if req.path().ends_with("/schedule") {
+            // The alarm is re-armed when an earlier timer is scheduled after a later one.
+            self.timers
+                .schedule("late", "greet", Duration::from_millis(400), &"late")
+                .await?;
+            self.timers
+                .schedule("early", "greet", Duration::from_millis(100), &"early")
+                .await?;
+            self.timers
+                .schedule("flaky", "fail", Duration::from_millis(100), &())
+                .await?;
+            self.timers
+                .schedule("garbled", "greet", Duration::from_millis(100), &42)
+                .await?;
+            return Response::from_json(&serde_json::json!({
+                "alarm": self.state.storage().get_alarm().await?,
+                "early": self.timers.scheduled_at("early")?,
+            }));
+        }
+
+        let kv = self.state.storage().kv();
+        let mut pending = Vec::new();
+        for name in TIMER_NAMES {
+            if self.timers.scheduled_at(name)?.is_some() {
+                pending.push(name);
+            }
+        }
+        Response::from_json(&serde_json::json!({
+            "fired": kv.get::<Vec<String>>("fired")?.unwrap_or_default(),
+            "attempts": kv.get::<u32>("attempts")?.unwrap_or_default(),
+            "pending": pending,
+            "alarm": self.state.storage().get_alarm().await?,
+        }))
+    }
+
+    async fn alarm(&self) -> Result<Response> {
+        let kv = self.state.storage().kv();
+        self.timers
+            .dispatch()
+            .on("greet", |timer: Timer<String>| {
+                let kv = kv.clone();
+                async move {
+                    let mut fired: Vec<String> = kv.get("fired")?.unwrap_or_default();
+                    fired.push(timer.payload);
+                    kv.put("fired", fired)
+                }
+            })
+            .on("fail", |timer: Timer<()>| {
+                let kv = kv.clone();
+                async move {
+                    kv.put("attempts", timer.retries + 1)?;
+                    Err("always fails".into())
+                }
+            })
+            .run()
+            .await?;
+        Response::ok("ok")
+    }
+}
+
+#[worker::send]
+pub async fn handle_timers(req: Request, env: Env, _data: SomeSharedData) -> Result<Response> {
+    let url = req.url()?;
+    let mut segments = url.path_segments().unwrap().skip(1);
+    let (Some(name), Some(action)) = (segments.next(), segments.next()) else {
+        return Response::error("Bad Request", 400);
+    };
+    let namespace = env.durable_object("TIMERS")?;
+    let stub = namespace.id_from_name(name)?.get_stub()?;
+    stub.fetch_with_str(&format!("https://fake-host/{action}"))
+        .await
+}
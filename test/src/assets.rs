@@ -35,3 +35,16 @@ pub async fn handle_asset(
         .await?
         .try_into()
 }
+
+#[worker::send]
+pub async fn handle_spa(
+    req: worker::Request,
+    env: worker::Env,
+    _data: crate::SomeSharedData,
+) -> worker::Result<worker::Response> {
+    worker::Assets::new("ASSETS")
+        .spa_fallback("/spa/index.html")
+        .not_found_page("/spa/404.html")
+        .serve(req, &env)
+        .await
+}
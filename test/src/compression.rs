@@ -0,0 +1,55 @@
+use worker::{CompressionFormat, Env, Request, Response, Result};
+
+use crate::SomeSharedData;
+
+fn body() -> String {
+    "The quick brown fox jumps over the lazy dog. ".repeat(100)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn handle_compress(req: Request, _env: Env, _data: SomeSharedData) -> Result<Response> {
+    Response::ok(body())?.compress(&req)
+}
+
+#[worker::send]
+pub async fn handle_compression_round_trip(
+    req: Request,
+    _env: Env,
+    _data: SomeSharedData,
+) -> Result<Response> {
+    let url = req.url()?;
+    let format = match url.path_segments().unwrap().nth(2) {
+        Some("gzip") => CompressionFormat::Gzip,
+        Some("deflate") => CompressionFormat::Deflate,
+        Some("deflate-raw") => CompressionFormat::DeflateRaw,
+        _ => return Response::error("Unknown format", 400),
+    };
+
+    let mut compressed = match Response::ok(body())?.compress_with(format) {
+        Ok(compressed) => compressed,
+        Err(e) => return Response::error(e.to_string(), 400),
+    };
+    let coding = compressed.headers().get("Content-Encoding")?.unwrap();
+    let bytes = compressed.bytes().await?;
+    let compressed_len = bytes.len();
+
+    let mut received = Response::from_bytes(bytes)?;
+    received.headers_mut().set("Content-Encoding", &coding)?;
+    let mut decompressed = received.decompress()?;
+    let text = decompressed.text().await?;
+    if text != body() {
+        return Response::error("Round trip changed the body", 500);
+    }
+
+    let mut resp = Response::ok(text)?;
+    resp.headers_mut()
+        .set("X-Compressed-Length", &compressed_len.to_string())?;
+    resp.headers_mut().set(
+        "X-Decompressed-Encoding",
+        &decompressed
+            .headers()
+            .get("Content-Encoding")?
+            .unwrap_or_default(),
+    )?;
+    Ok(resp)
+}
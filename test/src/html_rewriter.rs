@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use worker::html_rewriter::{
+    ContentType, Doctype, DocumentContentHandlers, DocumentEnd, Element, ElementContentHandlers,
+    TextChunk,
+};
+use worker::{Delay, Env, Error, HtmlRewriter, Request, Response, Result};
+
+use crate::SomeSharedData;
+
+const PAGE: &str = "<!DOCTYPE html>\
+<html><body>\
+<!-- replace me -->\
+<a href=\"https://example.com\">Example</a>\
+<p class=\"note\">hello <b>world</b></p>\
+<img data-src=\"/cat.png\">\
+<ul><li>one</li><li>two</li></ul>\
+</body></html>";
+
+#[derive(Default)]
+struct ListItems {
+    count: usize,
+}
+
+impl ElementContentHandlers for ListItems {
+    fn element(&mut self, element: &mut Element) -> Result<()> {
+        self.count += 1;
+        element.set_attribute("data-index", &self.count.to_string())
+    }
+
+    fn text(&mut self, text: &mut TextChunk) -> Result<()> {
+        if !text.text().is_empty() {
+            text.replace(&text.text().to_uppercase(), ContentType::Text)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Document {
+    doctype: Option<String>,
+}
+
+impl DocumentContentHandlers for Document {
+    fn doctype(&mut self, doctype: &mut Doctype) -> Result<()> {
+        self.doctype = doctype.name();
+        Ok(())
+    }
+
+    fn end(&mut self, end: &mut DocumentEnd) -> Result<()> {
+        end.append(
+            &format!(
+                "<!-- doctype={} -->",
+                self.doctype.as_deref().unwrap_or("none")
+            ),
+            ContentType::Html,
+        )
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn handle_html_rewriter(_req: Request, _env: Env, _data: SomeSharedData) -> Result<Response> {
+    HtmlRewriter::new()
+        .on_element("a[href^='http']", |el| {
+            el.set_attribute("rel", "noopener")?;
+            el.prepend("&rarr; ", ContentType::Html)
+        })
+        .on_element("p.note", |el| {
+            el.on_end_tag(|end| end.after("<hr>", ContentType::Html))
+        })
+        .on_comment_async("body", |comment| async move {
+            Delay::from(Duration::from_millis(10)).await;
+            comment.replace("<!-- rewritten -->", ContentType::Html)
+        })
+        .on_text("b", |text| {
+            if text.text() == "world" {
+                text.replace("<rewriter>", ContentType::Text)?;
+            }
+            Ok(())
+        })
+        .on_element_async("img[data-src]", |el| async move {
+            Delay::from(Duration::from_millis(10)).await;
+            if let Some(src) = el.get_attribute("data-src") {
+                el.set_attribute("src", &src)?;
+                el.remove_attribute("data-src");
+            }
+            Ok(())
+        })
+        .on("li", ListItems::default())
+        .on_document(Document::default())
+        .transform(Response::from_html(PAGE)?)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn handle_html_rewriter_error(
+    _req: Request,
+    _env: Env,
+    _data: SomeSharedData,
+) -> Result<Response> {
+    HtmlRewriter::new()
+        .on_element("p", |_| Err(Error::RustError("rewriter failed".into())))
+        .transform(Response::from_html(PAGE)?)
+}
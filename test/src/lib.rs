@@ -17,14 +17,17 @@ mod analytics_engine;
 mod assets;
 mod auto_response;
 mod cache;
+mod compression;
 mod container;
 mod counter;
 mod d1;
 mod durable;
 mod fetch;
 mod form;
+mod html_rewriter;
 mod js_snippets;
 mod kv;
+mod middleware;
 mod put_raw;
 mod queue;
 mod r2;
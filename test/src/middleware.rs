@@ -0,0 +1,64 @@
+use worker::async_trait::async_trait;
+use worker::{Cors, Env, Middleware, Request, Response, Result, RouteContext, Router};
+
+use crate::SomeSharedData;
+
+/// The names of the middleware that ran before the handler, in order.
+type Trace = Vec<&'static str>;
+
+/// Records its name in the [`Trace`] before the handler, and in the `x-after` header after it.
+struct Tag(&'static str);
+
+#[async_trait(?Send)]
+impl Middleware<Trace> for Tag {
+    async fn before(
+        &self,
+        _req: &mut Request,
+        ctx: &mut RouteContext<Trace>,
+    ) -> Result<Option<Response>> {
+        ctx.data.push(self.0);
+        Ok(None)
+    }
+
+    async fn after(&self, res: Response) -> Result<Response> {
+        let headers = res.headers().clone();
+        headers.append("x-after", self.0)?;
+        Ok(res.with_headers(headers))
+    }
+}
+
+struct RequireToken;
+
+#[async_trait(?Send)]
+impl Middleware<Trace> for RequireToken {
+    async fn before(
+        &self,
+        req: &mut Request,
+        _ctx: &mut RouteContext<Trace>,
+    ) -> Result<Option<Response>> {
+        match req.headers().get("Authorization")? {
+            Some(_) => Ok(None),
+            None => Response::error("Unauthorized", 401).map(Some),
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn trace(_req: Request, ctx: RouteContext<Trace>) -> Result<Response> {
+    Response::ok(ctx.data.join(","))
+}
+
+#[worker::send]
+pub async fn handle_middleware(req: Request, env: Env, _data: SomeSharedData) -> Result<Response> {
+    Router::with_data(Trace::new())
+        .middleware(Cors::new().with_origins(["https://example.com"]))
+        .middleware(Tag("global"))
+        .get("/middleware/public", trace)
+        .group(Tag("group"), |router| {
+            router.group(RequireToken, |router| {
+                router.get("/middleware/admin", trace)
+            })
+        })
+        .run(req, env)
+        .await
+}
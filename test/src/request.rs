@@ -8,7 +8,7 @@ use serde::Serialize;
 use std::time::Duration;
 use worker::Env;
 use worker::{
-    console_log, ok, Date, Delay, Request, Response, ResponseBody, ResponseBuilder, Result,
+    console_log, ok, Date, Delay, Error, Request, Response, ResponseBody, ResponseBuilder, Result,
 };
 
 #[allow(clippy::needless_pass_by_value)]
@@ -90,6 +90,18 @@ pub async fn handle_async_text_echo(
     Response::ok(req.text().await?)
 }
 
+pub async fn handle_cloned_body_limit(
+    mut req: Request,
+    _env: Env,
+    _data: SomeSharedData,
+) -> Result<Response> {
+    req.set_body_limit(Some(8));
+    match req.clone()?.text().await {
+        Err(Error::PayloadTooLarge { limit }) => Response::error(format!("limit {limit}"), 413),
+        res => Response::ok(res?),
+    }
+}
+
 pub async fn handle_secret(_req: Request, env: Env, _data: SomeSharedData) -> Result<Response> {
     Response::ok(env.secret("SOME_SECRET")?.to_string())
 }
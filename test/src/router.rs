@@ -1,9 +1,9 @@
 use crate::signal;
 use crate::{
-    alarm, analytics_engine, assets, auto_response, cache, container, counter, d1, durable, fetch,
-    form, js_snippets, kv, put_raw, queue, r2, rate_limit, request, secret_store, send_email,
-    service, socket, sql_counter, sql_iterator, user, ws, SomeSharedData, GLOBAL_SECOND_START,
-    GLOBAL_STATE,
+    alarm, analytics_engine, assets, auto_response, cache, compression, container, counter, d1,
+    durable, fetch, form, html_rewriter, js_snippets, kv, middleware, put_raw, queue, r2,
+    rate_limit, request, secret_store, send_email, service, socket, sql_counter, sql_iterator,
+    user, ws, SomeSharedData, GLOBAL_SECOND_START, GLOBAL_STATE,
 };
 #[cfg(feature = "http")]
 use std::convert::TryInto;
@@ -16,7 +16,7 @@ use worker::{RouteContext, Router};
 
 #[cfg(feature = "http")]
 use axum::{
-    routing::{delete, get, head, options, patch, post, put},
+    routing::{any, delete, get, head, options, patch, post, put},
     Extension,
 };
 
@@ -91,6 +91,9 @@ macro_rules! handler (
 // https://github.com/rust-lang/rfcs/pull/2442.)
 #[cfg(feature = "http")]
 macro_rules! add_route (
+    ($obj:ident, any, $route:expr, $name:path) => {
+        let $obj = $obj.route($route, any(handler!($name)));
+    };
     ($obj:ident, $method:ident, sync, $route:expr, $name:path) => {
         let $obj = $obj.route($route, $method(handler!($name, sync)));
     };
@@ -102,6 +105,9 @@ macro_rules! add_route (
 // the format for worker::Router's async methods.
 #[cfg(not(feature = "http"))]
 macro_rules! add_route (
+    ($obj:ident, any, $route:expr, $name:path) => {
+        let $obj = $obj.on_async($route, handler!($name));
+    };
     ($obj:ident, $method:ident, $route:expr, $name:path) => {
         paste::item! {
             let $obj = $obj.[<$method _ async>]($route, handler!($name));
@@ -118,6 +124,7 @@ macro_rules! add_routes (
     add_route!($obj, get, "/analytics-engine", analytics_engine::handle_analytics_event);
     add_route!($obj, get, "/async-request", request::handle_async_request);
     add_route!($obj, get, format_route!("/asset/{}", "name"), assets::handle_asset);
+    add_route!($obj, get, format_route!("/spa/{}", "*path"), assets::handle_spa);
     add_route!($obj, get, "/websocket", ws::handle_websocket);
     add_route!($obj, get, "/got-close-event", handle_close_event);
     add_route!($obj, get, "/ws-client",ws::handle_websocket_client);
@@ -134,10 +141,12 @@ macro_rules! add_routes (
     add_route!($obj, post, format_route!("/account/{}/zones", "id"), user::handle_post_account_id_zones);
     add_route!($obj, get, format_route!("/account/{}/zones","id"), user::handle_get_account_id_zones);
     add_route!($obj, post, "/async-text-echo",  request::handle_async_text_echo);
+    add_route!($obj, post, "/cloned-body-limit", request::handle_cloned_body_limit);
     add_route!($obj, get, "/fetch",fetch::handle_fetch);
     add_route!($obj, get, "/fetch_json",fetch::handle_fetch_json);
     add_route!($obj, get, format_route!("/proxy_request/{}", "*url") ,fetch::handle_proxy_request);
     add_route!($obj, get, "/durable/alarm", alarm::handle_alarm);
+    add_route!($obj, get, format_route!("/timers/{}", "*path"), alarm::handle_timers);
     add_route!($obj, get, format_route!("/durable/{}", "id"), counter::handle_id);
     add_route!($obj, get, "/durable/put-raw", put_raw::handle_put_raw);
     add_route!($obj, get, "/durable/websocket", counter::handle_websocket);
@@ -245,6 +254,13 @@ macro_rules! add_routes (
     add_route!($obj, get, "/rate-limit/reset", rate_limit::handle_rate_limit_reset);
     add_route!($obj, get, "/send-email", send_email::handle_send_email);
     add_route!($obj, get, "/signal/poll", signal::handle_signal_poll);
+    add_route!($obj, get, sync, "/html-rewriter", html_rewriter::handle_html_rewriter);
+    add_route!($obj, get, sync, "/html-rewriter/error", html_rewriter::handle_html_rewriter_error);
+    add_route!($obj, get, sync, "/compression/compress", compression::handle_compress);
+    add_route!($obj, get, format_route!("/compression/round-trip/{}", "format"), compression::handle_compression_round_trip);
+    add_route!($obj, any, format_route!("/router/dispatch/{}", "*path"), handle_router_dispatch);
+    add_route!($obj, any, format_route!("/router/errors/{}", "*path"), handle_router_errors);
+    add_route!($obj, any, format_route!("/middleware/{}", "*path"), middleware::handle_middleware);
 });
 
 #[cfg(feature = "http")]
@@ -277,6 +293,45 @@ async fn respond_async(req: Request, _env: Env, _data: SomeSharedData) -> Result
         .ok(format!("Ok (async): {}", String::from(req.method())))
 }
 
+// Runs a `worker::Router` for the `/router/dispatch` routes, whatever the main router is, to
+// check how it answers methods without a handler.
+#[worker::send]
+async fn handle_router_dispatch(req: Request, env: Env, _data: SomeSharedData) -> Result<Response> {
+    worker::Router::new()
+        .get("/router/dispatch/item", |_, _| {
+            ResponseBuilder::new()
+                .with_header("x-handler", "get")?
+                .ok("item")
+        })
+        .post("/router/dispatch/item", |_, _| Response::ok("created"))
+        .head("/router/dispatch/head-only", |_, _| Response::empty())
+        .cors_preflight(worker::Cors::new().with_origins(["https://example.com"]))
+        .run(req, env)
+        .await
+}
+
+// Runs a `worker::Router` with custom "Not Found", "Method Not Allowed" and error responses.
+#[worker::send]
+async fn handle_router_errors(req: Request, env: Env, _data: SomeSharedData) -> Result<Response> {
+    worker::Router::new()
+        .get("/router/errors/item", |_, _| Response::ok("item"))
+        .get("/router/errors/fail", |_, _| Err("handler failed".into()))
+        .not_found(|req, _| Response::error(format!("nothing at {}", req.path()), 404))
+        .method_not_allowed(|req, _| {
+            Response::error(
+                format!("{} is not allowed", String::from(req.method())),
+                405,
+            )
+        })
+        .on_error(|err| {
+            let body =
+                serde_json::json!({ "brand": "testing-rust-worker", "error": err.to_string() });
+            Ok(Response::from_json(&body)?.with_status(500))
+        })
+        .run(req, env)
+        .await
+}
+
 #[worker::send]
 async fn handle_close_event(_req: Request, env: Env, _data: SomeSharedData) -> Result<Response> {
     let some_namespace_kv = env.kv("SOME_NAMESPACE")?;
@@ -1,5 +1,6 @@
 use worker::{
     durable_object, wasm_bindgen, DurableObject, Env, Request, Response, Result, SqlStorage, State,
+    Storage,
 };
 
 /// A simple SQLite-backed counter stored in Durable Object storage.
@@ -9,6 +10,7 @@ use worker::{
 #[durable_object]
 pub struct SqlCounter {
     sql: SqlStorage,
+    storage: Storage,
 }
 
 impl DurableObject for SqlCounter {
@@ -17,7 +19,10 @@ impl DurableObject for SqlCounter {
         // Create table if it does not exist.  Note: `exec` is synchronous.
         sql.exec("CREATE TABLE IF NOT EXISTS counter(value INTEGER);", None)
             .expect("create table");
-        Self { sql }
+        Self {
+            sql,
+            storage: state.storage(),
+        }
     }
 
     async fn fetch(&self, req: Request) -> Result<Response> {
@@ -27,6 +32,10 @@ impl DurableObject for SqlCounter {
         // Parse path to determine action
         if path.contains("/set-large/") {
             self.handle_set_large_value(&url)
+        } else if path.ends_with("/transaction/commit") {
+            self.handle_transaction(true)
+        } else if path.ends_with("/transaction/rollback") {
+            self.handle_transaction(false)
         } else {
             self.handle_increment()
         }
@@ -83,6 +92,43 @@ impl SqlCounter {
 
         Response::ok(format!("Successfully stored large value: {large_value}"))
     }
+
+    /// Write a SQL row and a KV key in a transaction that commits or fails, and report what
+    /// persisted.
+    fn handle_transaction(&self, commit: bool) -> Result<Response> {
+        #[derive(serde::Deserialize)]
+        struct Count {
+            n: i64,
+        }
+
+        self.sql
+            .exec("CREATE TABLE IF NOT EXISTS ledger(entry TEXT);", None)?;
+        let kv = self.storage.kv();
+        let outcome = self.storage.transaction_sync(|| {
+            self.sql.exec(
+                "INSERT INTO ledger(entry) VALUES (?);",
+                vec!["transfer".into()],
+            )?;
+            kv.put("last_transfer", "transfer")?;
+            if commit {
+                Ok(())
+            } else {
+                Err(worker::Error::from("transfer rejected"))
+            }
+        });
+
+        let rows: Vec<Count> = self
+            .sql
+            .exec("SELECT COUNT(*) AS n FROM ledger;", None)?
+            .to_array()?;
+        let last_transfer: Option<String> = kv.get("last_transfer")?;
+        Response::ok(format!(
+            "{}: rows={} kv={}",
+            outcome.map_or_else(|e| e.to_string(), |()| "committed".to_string()),
+            rows.first().map_or(0, |row| row.n),
+            last_transfer.as_deref().unwrap_or("none")
+        ))
+    }
 }
 
 #[worker::send]
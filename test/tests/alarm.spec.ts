@@ -0,0 +1,39 @@
+import { describe, test, expect } from "vitest";
+import { mf, mfUrl } from "./mf";
+
+type TimerState = {
+  fired: string[];
+  attempts: number;
+  pending: string[];
+  alarm: number | null;
+};
+
+describe("alarm scheduler", () => {
+  test("dispatches timers by kind, retrying and dropping failures", async () => {
+    let resp = await mf.dispatchFetch(`${mfUrl}timers/scheduler/schedule`);
+    expect(resp.status).toBe(200);
+    const { alarm, early } = (await resp.json()) as {
+      alarm: number;
+      early: number;
+    };
+    // Scheduling "early" after "late" re-armed the alarm to the earliest timer.
+    expect(alarm).toBe(early);
+
+    let state: TimerState | undefined;
+    for (let i = 0; i < 50; i++) {
+      await new Promise((resolve) => setTimeout(resolve, 100));
+      resp = await mf.dispatchFetch(`${mfUrl}timers/scheduler/state`);
+      state = (await resp.json()) as TimerState;
+      if (state.pending.length === 0) break;
+    }
+
+    // "garbled" has a payload its callback can't read, so it is dropped without firing, and
+    // "flaky" is dropped after failing twice.
+    expect(state).toEqual({
+      fired: ["early", "late"],
+      attempts: 2,
+      pending: [],
+      alarm: null,
+    });
+  });
+});
@@ -7,4 +7,24 @@ describe("assets", () => {
     const body = await resp.text();
     expect(body).toBe("TEST");
   });
+
+  test("spa fallback serves the app for deep links", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}spa/dashboard/settings`, {
+      headers: { "Sec-Fetch-Mode": "navigate", Accept: "text/html" },
+      redirect: "manual",
+    });
+    expect(resp.status).toBe(200);
+    expect(resp.headers.get("location")).toBeNull();
+    expect(await resp.text()).toContain("<title>SPA</title>");
+  });
+
+  test("not found page is served with a 404", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}spa/missing.js`, {
+      headers: { Accept: "*/*" },
+      redirect: "manual",
+    });
+    expect(resp.status).toBe(404);
+    expect(resp.headers.get("location")).toBeNull();
+    expect(await resp.text()).toContain("<h1>Nothing here</h1>");
+  });
 });
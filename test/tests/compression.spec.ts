@@ -0,0 +1,46 @@
+import { describe, test, expect } from "vitest";
+import { mf, mfUrl } from "./mf";
+
+const body = "The quick brown fox jumps over the lazy dog. ".repeat(100);
+
+describe("compression", () => {
+  test("compresses in the accepted encoding", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}compression/compress`, {
+      headers: { "Accept-Encoding": "deflate, gzip" },
+    });
+    expect(resp.status).toBe(200);
+    expect(resp.headers.get("content-encoding")).toBe("gzip");
+    expect(resp.headers.get("vary")).toContain("Accept-Encoding");
+    expect(await resp.text()).toBe(body);
+  });
+
+  test("leaves the body alone without an accepted encoding", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}compression/compress`, {
+      headers: { "Accept-Encoding": "identity" },
+    });
+    expect(resp.status).toBe(200);
+    expect(resp.headers.get("content-encoding")).toBeNull();
+    expect(resp.headers.get("vary")).toContain("Accept-Encoding");
+    expect(await resp.text()).toBe(body);
+  });
+
+  test.each(["gzip", "deflate"])("round trips %s", async (format) => {
+    const resp = await mf.dispatchFetch(
+      `${mfUrl}compression/round-trip/${format}`
+    );
+    expect(resp.status).toBe(200);
+    expect(await resp.text()).toBe(body);
+    const compressedLength = Number(resp.headers.get("x-compressed-length"));
+    expect(compressedLength).toBeGreaterThan(0);
+    expect(compressedLength).toBeLessThan(body.length);
+    expect(resp.headers.get("x-decompressed-encoding")).toBe("");
+  });
+
+  test("rejects deflate-raw as a content coding", async () => {
+    const resp = await mf.dispatchFetch(
+      `${mfUrl}compression/round-trip/deflate-raw`
+    );
+    expect(resp.status).toBe(400);
+    expect(await resp.text()).toContain("no HTTP content coding");
+  });
+});
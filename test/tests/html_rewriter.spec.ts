@@ -0,0 +1,31 @@
+import { describe, test, expect } from "vitest";
+import { mf, mfUrl } from "./mf";
+
+describe("html rewriter", () => {
+  test("rewrites elements, text, comments and the document", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}html-rewriter`);
+    expect(resp.status).toBe(200);
+    expect(resp.headers.get("content-type")).toContain("text/html");
+
+    const body = await resp.text();
+    expect(body).toContain(
+      '<a href="https://example.com" rel="noopener">&rarr; Example</a>'
+    );
+    expect(body).toContain(
+      '<p class="note">hello <b>&lt;rewriter&gt;</b></p><hr>'
+    );
+    expect(body).not.toContain("replace me");
+    expect(body).toContain("<body><!-- rewritten --><a ");
+    expect(body).toContain('<img src="/cat.png">');
+    expect(body).toContain(
+      '<ul><li data-index="1">ONE</li><li data-index="2">TWO</li></ul>'
+    );
+    expect(body.endsWith("</html><!-- doctype=html -->")).toBe(true);
+  });
+
+  test("handler errors fail the body stream", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}html-rewriter/error`);
+    expect(resp.status).toBe(200);
+    await expect(resp.text()).rejects.toThrow();
+  });
+});
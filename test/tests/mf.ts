@@ -75,6 +75,10 @@ const mf_instance = new Miniflare({
           className: "SqlIterator",
           useSQLite: true,
         },
+        TIMERS: {
+          className: "TimerObject",
+          useSQLite: true,
+        },
       },
       kvNamespaces: ["SOME_NAMESPACE", "FILE_SIZES", "TEST"],
       serviceBindings: {
@@ -0,0 +1,51 @@
+import { describe, test, expect } from "vitest";
+import { mf, mfUrl } from "./mf";
+
+describe("middleware", () => {
+  test("global middleware runs around every route", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}middleware/public`);
+    expect(resp.status).toBe(200);
+    expect(await resp.text()).toBe("global");
+    expect(resp.headers.get("x-after")).toBe("global");
+  });
+
+  test("group middleware runs inside global middleware", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}middleware/admin`, {
+      headers: { Authorization: "Bearer token" },
+    });
+    expect(resp.status).toBe(200);
+    expect(await resp.text()).toBe("global,group");
+    expect(resp.headers.get("x-after")).toBe("group, global");
+  });
+
+  test("before can short-circuit the handler", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}middleware/admin`);
+    expect(resp.status).toBe(401);
+    expect(await resp.text()).toBe("Unauthorized");
+    // The middleware outside the one that short-circuited still post-process the response.
+    expect(resp.headers.get("x-after")).toBe("group, global");
+  });
+
+  test("cors middleware answers preflights and decorates responses", async () => {
+    let resp = await mf.dispatchFetch(`${mfUrl}middleware/public`, {
+      method: "OPTIONS",
+      headers: {
+        Origin: "https://example.com",
+        "Access-Control-Request-Method": "GET",
+      },
+    });
+    expect(resp.status).toBe(204);
+    expect(resp.headers.get("access-control-allow-origin")).toBe(
+      "https://example.com"
+    );
+    expect(resp.headers.get("x-after")).toBeNull();
+
+    resp = await mf.dispatchFetch(`${mfUrl}middleware/public`, {
+      headers: { Origin: "https://example.com" },
+    });
+    expect(resp.status).toBe(200);
+    expect(resp.headers.get("access-control-allow-origin")).toBe(
+      "https://example.com"
+    );
+  });
+});
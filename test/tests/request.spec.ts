@@ -96,6 +96,21 @@ test("async text echo", async () => {
   expect(await resp.text()).toBe("Example text!");
 });
 
+test("cloned requests keep the body limit", async () => {
+  let resp = await mf.dispatchFetch(`${mfUrl}cloned-body-limit`, {
+    method: "POST",
+    body: "short",
+  });
+  expect(await resp.text()).toBe("short");
+
+  resp = await mf.dispatchFetch(`${mfUrl}cloned-body-limit`, {
+    method: "POST",
+    body: "longer than the limit",
+  });
+  expect(resp.status).toBe(413);
+  expect(await resp.text()).toBe("limit 8");
+});
+
 test("fetch", async () => {
   const resp = await mf.dispatchFetch(`${mfUrl}fetch`);
   expect(resp.status).toBe(200);
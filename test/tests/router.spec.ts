@@ -0,0 +1,79 @@
+import { describe, test, expect } from "vitest";
+import { mf, mfUrl } from "./mf";
+
+describe("router dispatch", () => {
+  const item = `${mfUrl}router/dispatch/item`;
+
+  test("HEAD falls back to the GET handler without a body", async () => {
+    const resp = await mf.dispatchFetch(item, { method: "HEAD" });
+    expect(resp.status).toBe(200);
+    expect(resp.headers.get("x-handler")).toBe("get");
+    expect(await resp.text()).toBe("");
+  });
+
+  test("OPTIONS lists the allowed methods", async () => {
+    const resp = await mf.dispatchFetch(item, { method: "OPTIONS" });
+    expect(resp.status).toBe(204);
+    expect(resp.headers.get("allow")).toBe("HEAD, GET, POST, OPTIONS");
+  });
+
+  test("unhandled methods get a 405 with Allow", async () => {
+    const resp = await mf.dispatchFetch(item, { method: "DELETE" });
+    expect(resp.status).toBe(405);
+    expect(resp.headers.get("allow")).toBe("HEAD, GET, POST, OPTIONS");
+  });
+
+  test("HEAD alone doesn't make a path exist for other methods", async () => {
+    const headOnly = `${mfUrl}router/dispatch/head-only`;
+    let resp = await mf.dispatchFetch(headOnly, { method: "POST" });
+    expect(resp.status).toBe(404);
+    expect(resp.headers.get("allow")).toBeNull();
+
+    resp = await mf.dispatchFetch(headOnly, { method: "OPTIONS" });
+    expect(resp.status).toBe(204);
+    expect(resp.headers.get("allow")).toBe("HEAD, OPTIONS");
+  });
+
+  test("CORS preflights are answered from the policy", async () => {
+    const resp = await mf.dispatchFetch(item, {
+      method: "OPTIONS",
+      headers: {
+        Origin: "https://example.com",
+        "Access-Control-Request-Method": "POST",
+      },
+    });
+    expect(resp.status).toBe(204);
+    expect(resp.headers.get("access-control-allow-origin")).toBe(
+      "https://example.com"
+    );
+    expect(resp.headers.get("access-control-allow-methods")).toBe(
+      "HEAD,GET,POST,OPTIONS"
+    );
+  });
+});
+
+describe("router error responses", () => {
+  test("not found handler", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}router/errors/missing`);
+    expect(resp.status).toBe(404);
+    expect(await resp.text()).toBe("nothing at /router/errors/missing");
+  });
+
+  test("method not allowed handler still gets Allow", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}router/errors/item`, {
+      method: "PUT",
+    });
+    expect(resp.status).toBe(405);
+    expect(resp.headers.get("allow")).toBe("HEAD, GET, OPTIONS");
+    expect(await resp.text()).toBe("PUT is not allowed");
+  });
+
+  test("handler errors are rendered by on_error", async () => {
+    const resp = await mf.dispatchFetch(`${mfUrl}router/errors/fail`);
+    expect(resp.status).toBe(500);
+    expect(await resp.json()).toEqual({
+      brand: "testing-rust-worker",
+      error: "handler failed",
+    });
+  });
+});
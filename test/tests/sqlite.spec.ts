@@ -66,4 +66,23 @@ describe("sqlite durable object", () => {
     expect(text).toContain(unsafeNegativeValue);
     expect(text).toContain("JavaScript safe range is ±9007199254740991");
   });
+
+  test("transaction_sync rolls back SQL and KV writes on error", async () => {
+    let resp = await mf.dispatchFetch(
+      `${mfUrl}sql-counter/tx-rollback/transaction/rollback`,
+    );
+    expect(resp.status).toBe(200);
+    expect(await resp.text()).toBe("transfer rejected: rows=0 kv=none");
+
+    resp = await mf.dispatchFetch(
+      `${mfUrl}sql-counter/tx-commit/transaction/commit`,
+    );
+    expect(await resp.text()).toBe("committed: rows=1 kv=transfer");
+
+    // A failed transaction leaves earlier committed writes alone.
+    resp = await mf.dispatchFetch(
+      `${mfUrl}sql-counter/tx-commit/transaction/rollback`,
+    );
+    expect(await resp.text()).toBe("transfer rejected: rows=1 kv=transfer");
+  });
 });
@@ -1,6 +1,9 @@
 use proc_macro2::{Ident, TokenStream};
-use quote::quote;
-use syn::{Error, ItemImpl, ItemStruct};
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, Error, FnArg, GenericArgument, ImplItem, ImplItemFn, ItemImpl, ItemStruct,
+    Pat, PathArguments, Receiver, ReceiverKind, ReturnType, Type, Visibility,
+};
 
 enum DurableObjectType {
     Fetch,
@@ -148,14 +151,258 @@ mod bindgen_methods {
     }
 }
 
-pub fn expand_macro(attr: TokenStream, tokens: TokenStream) -> syn::Result<TokenStream> {
-    // Try to give a nice error for previous impl usage
-    if syn::parse2::<ItemImpl>(tokens.clone()).is_ok() {
-        return Err(syn::Error::new(
-            proc_macro2::Span::call_site(),
-            "The #[durable_object] macro is no longer required for `impl` blocks, and can be removed"
+/// JS names of the methods exported by `bindgen_methods`, which RPC methods must not shadow.
+const RESERVED_NAMES: &[&str] = &[
+    "constructor",
+    "fetch",
+    "alarm",
+    "webSocketMessage",
+    "webSocketClose",
+    "webSocketError",
+];
+
+struct RpcMethod<'a> {
+    method: &'a ImplItemFn,
+    js_name: String,
+    args: Vec<(&'a Ident, &'a Type)>,
+    output: &'a Type,
+}
+
+impl<'a> RpcMethod<'a> {
+    fn parse(method: &'a ImplItemFn) -> syn::Result<Self> {
+        let sig = &method.sig;
+        match sig.inputs.first() {
+            Some(FnArg::Receiver(Receiver {
+                kind: ReceiverKind::Reference(_, _, None),
+                ..
+            })) => {}
+            _ => {
+                return Err(Error::new(
+                    sig.span(),
+                    "RPC methods must take `&self` as their first argument",
+                ))
+            }
+        }
+        if !sig.generics.params.is_empty() {
+            return Err(Error::new(
+                sig.generics.span(),
+                "RPC methods can't be generic",
+            ));
+        }
+
+        let mut args = Vec::new();
+        for arg in sig.inputs.iter().skip(1) {
+            let FnArg::Typed(arg) = arg else {
+                unreachable!("only the first argument can be a receiver")
+            };
+            let Pat::Ident(pat) = &*arg.pat else {
+                return Err(Error::new(
+                    arg.pat.span(),
+                    "RPC method arguments must be plain identifiers",
+                ));
+            };
+            if let Type::Reference(ty) = &*arg.ty {
+                return Err(Error::new(
+                    ty.span(),
+                    "RPC method arguments must be owned, as they are deserialized from the caller's values",
+                ));
+            }
+            args.push((&pat.ident, &*arg.ty));
+        }
+
+        let output = match &sig.output {
+            ReturnType::Type(_, ty) => result_ok_type(ty),
+            ReturnType::Default => None,
+        }
+        .ok_or_else(|| {
+            Error::new(
+                sig.output.span(),
+                "RPC methods must return `worker::Result<T>`",
+            )
+        })?;
+
+        let js_name = camel_case(&sig.ident.to_string());
+        if RESERVED_NAMES.contains(&js_name.as_str()) {
+            return Err(Error::new(
+                sig.ident.span(),
+                format!("`{js_name}` is reserved for the Durable Object's handlers"),
+            ));
+        }
+
+        Ok(Self {
+            method,
+            js_name,
+            args,
+            output,
+        })
+    }
+
+    /// The method exported to JavaScript, deserializing the arguments and serializing the
+    /// result of the Rust method.
+    fn export(&self) -> TokenStream {
+        let name = &self.method.sig.ident;
+        let export_name = format_ident!("__rpc_{}", name);
+        let js_name = &self.js_name;
+        let arg_names = self.args.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let call = match self.method.sig.asyncness {
+            Some(_) => quote!(static_self.#name(#(#arg_names),*).await),
+            None => quote!(static_self.#name(#(#arg_names),*)),
+        };
+        quote! {
+            #[wasm_bindgen(js_name = #js_name, wasm_bindgen=::worker::wasm_bindgen)]
+            pub fn #export_name(
+                &self,
+                #(#arg_names: ::worker::wasm_bindgen::JsValue),*
+            ) -> ::worker::js_sys::Promise {
+                // SAFETY:
+                // Durable Object will never be destroyed while there is still
+                // a running promise inside of it, therefore we can let a reference
+                // to the durable object escape into a static-lifetime future.
+                let static_self: &'static Self = unsafe { &*(self as *const _) };
+
+                ::worker::js_sys::futures::future_to_promise(::std::panic::AssertUnwindSafe(async move {
+                    let result: ::worker::Result<::worker::wasm_bindgen::JsValue> = async move {
+                        #(let #arg_names = ::worker::durable::rpc::from_js(#arg_names)?;)*
+                        ::worker::durable::rpc::to_js(&#call?)
+                    }.await;
+                    result.map_err(::worker::wasm_bindgen::JsValue::from)
+                }))
+            }
+        }
+    }
+
+    /// The method of the typed stub, calling the exported method through a `Stub`.
+    fn stub(&self) -> TokenStream {
+        let name = &self.method.sig.ident;
+        let js_name = &self.js_name;
+        let docs = self
+            .method
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"));
+        let args = self.args.iter().map(|(name, ty)| quote!(#name: #ty));
+        let arg_names = self.args.iter().map(|(name, _)| name);
+        let output = self.output;
+        quote! {
+            #(#docs)*
+            pub async fn #name(&self, #(#args),*) -> ::worker::Result<#output> {
+                let args = [#(::worker::durable::rpc::to_js(&#arg_names)?),*];
+                self.0.call(#js_name, &args).await
+            }
+        }
+    }
+}
+
+/// The `T` of a `Result<T>` or `Result<T, E>` type.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.trim_start_matches('_').chars() {
+        if c == '_' {
+            upper = !camel.is_empty();
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+/// Export the public `&self` methods of an `impl` block of a Durable Object as JavaScript RPC
+/// methods, and generate a typed stub to call them with.
+fn expand_rpc(item: ItemImpl) -> syn::Result<TokenStream> {
+    if item.trait_.is_some() || !item.generics.params.is_empty() {
+        return Err(Error::new(
+            item.span(),
+            "#[durable_object(rpc)] must be used on a non-generic inherent `impl` block",
         ));
     }
+    let target_name = match &*item.self_ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last().map(|s| &s.ident),
+        _ => None,
+    }
+    .ok_or_else(|| Error::new(item.self_ty.span(), "expected a Durable Object type"))?;
+
+    let methods = item
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Fn(method) if matches!(method.vis, Visibility::Public(_)) => Some(method),
+            _ => None,
+        })
+        .map(RpcMethod::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let exports = methods.iter().map(RpcMethod::export);
+    let stub_methods = methods.iter().map(RpcMethod::stub);
+
+    let stub_name = format_ident!("{}Stub", target_name);
+    let stub_doc = format!(
+        "A typed [`Stub`](::worker::Stub) for calling the RPC methods of [`{target_name}`]."
+    );
+    Ok(quote! {
+        #item
+
+        const _: () = {
+            use ::worker::wasm_bindgen::prelude::*;
+
+            #[wasm_bindgen(wasm_bindgen=::worker::wasm_bindgen)]
+            impl #target_name {
+                #(#exports)*
+            }
+        };
+
+        #[doc = #stub_doc]
+        #[derive(Debug)]
+        pub struct #stub_name(::worker::Stub);
+
+        impl ::std::convert::From<::worker::Stub> for #stub_name {
+            fn from(stub: ::worker::Stub) -> Self {
+                Self(stub)
+            }
+        }
+
+        impl #stub_name {
+            /// The untyped stub.
+            pub fn into_inner(self) -> ::worker::Stub {
+                self.0
+            }
+
+            #(#stub_methods)*
+        }
+    })
+}
+
+pub fn expand_macro(attr: TokenStream, tokens: TokenStream) -> syn::Result<TokenStream> {
+    if let Ok(item) = syn::parse2::<ItemImpl>(tokens.clone()) {
+        return match syn::parse2::<Ident>(attr) {
+            Ok(ident) if ident == "rpc" => expand_rpc(item),
+            // Try to give a nice error for previous impl usage
+            _ => Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "The #[durable_object] macro is no longer required for `impl` blocks, and can be removed. \
+                 Use #[durable_object(rpc)] to export an `impl` block's methods as RPC methods"
+            )),
+        };
+    }
 
     let target = syn::parse2::<ItemStruct>(tokens)?;
 
@@ -1,5 +1,7 @@
 mod durable_object;
 mod event;
+mod migrations;
+mod row;
 mod send;
 
 use proc_macro::TokenStream;
@@ -53,6 +55,28 @@ use proc_macro::TokenStream;
 ///     env: Env, // access `Env` across requests, use inside `fetch`
 /// }
 /// ```
+///
+/// ## RPC
+///
+/// Used as `#[durable_object(rpc)]` on an `impl` block of the struct, the macro exports its
+/// public methods taking `&self` and returning `Result<T>` as [JavaScript RPC](https://developers.cloudflare.com/durable-objects/best-practices/create-durable-object-stubs-and-send-requests/#invoke-rpc-methods)
+/// methods, with their names in camelCase. Arguments and results are converted with `serde`, so
+/// they must be owned types implementing `Deserialize` and `Serialize`.
+///
+/// A typed stub named after the struct, `ChatroomStub` below, is generated to call the methods
+/// from another Worker or Durable Object:
+///
+/// ```rust
+/// #[durable_object(rpc)]
+/// impl Chatroom {
+///     pub async fn post(&self, user: String, message: String) -> Result<usize> {
+///         // ...
+///     }
+/// }
+///
+/// let stub: ChatroomStub = env.durable_object("CHATROOM")?.get_by_name("lobby")?.into();
+/// let count = stub.post("ada".into(), "hello".into()).await?;
+/// ```
 #[proc_macro_attribute]
 pub fn durable_object(attr: TokenStream, item: TokenStream) -> TokenStream {
     durable_object::expand_macro(attr.into(), item.into())
@@ -136,6 +160,55 @@ pub fn send(attr: TokenStream, stream: TokenStream) -> TokenStream {
     send::expand_macro(attr, stream)
 }
 
+/// Embed the SQL migrations of a directory, relative to the crate's `Cargo.toml`, as a
+/// `&'static [worker::Migration]` sorted by version.
+///
+/// Migration files must be named `<version>_<name>.sql`, e.g. `0001_create_users.sql`. Changes
+/// to existing files trigger a rebuild, but adding a file does not, unless something else
+/// changes.
+///
+/// ```rust
+/// const MIGRATIONS: &[Migration] = embed_migrations!("migrations");
+/// ```
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    migrations::expand_macro(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `worker::FromRow`, converting each field from the column of the same name.
+///
+/// Use `#[sql(rename = "column")]` to read a field from a column with another name, and
+/// `#[sql(skip)]` to set it to its default value instead. Tuple structs are converted from the
+/// columns in order.
+///
+/// ```rust
+/// #[derive(FromRow)]
+/// struct User {
+///     id: i64,
+///     #[sql(rename = "display_name")]
+///     name: String,
+///     avatar: Option<Vec<u8>>,
+/// }
+/// ```
+#[proc_macro_derive(FromRow, attributes(sql))]
+pub fn from_row(input: TokenStream) -> TokenStream {
+    row::expand_from_row(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `worker::ToParams`, binding the fields as query parameters in order.
+///
+/// Fields marked `#[sql(skip)]` are not bound.
+#[proc_macro_derive(ToParams, attributes(sql))]
+pub fn to_params(input: TokenStream) -> TokenStream {
+    row::expand_to_params(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 #[doc(hidden)]
 #[proc_macro_attribute]
 pub fn consume(_: TokenStream, _: TokenStream) -> TokenStream {
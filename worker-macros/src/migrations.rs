@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Error, LitStr};
+
+struct MigrationFile {
+    version: u32,
+    name: String,
+    path: PathBuf,
+}
+
+/// Parse a migration file name of the form `<version>_<name>.sql`, e.g. `0001_create_users.sql`.
+fn parse_file_name(file_name: &str) -> Option<(u32, String)> {
+    let stem = file_name.strip_suffix(".sql")?;
+    let (version, name) = match stem.split_once('_') {
+        Some((version, name)) => (version, name),
+        None => (stem, ""),
+    };
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((version.parse().ok()?, name.to_string()))
+}
+
+fn read_migrations(dir: &Path, span: Span) -> syn::Result<Vec<MigrationFile>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        Error::new(
+            span,
+            format!("failed to read migrations from {}: {e}", dir.display()),
+        )
+    })?;
+
+    let mut migrations = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| Error::new(span, format!("failed to read migrations: {e}")))?
+            .path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !file_name.ends_with(".sql") {
+            continue;
+        }
+        let (version, name) = parse_file_name(file_name).ok_or_else(|| {
+            Error::new(
+                span,
+                format!("migration file names must look like `0001_name.sql`, found `{file_name}`"),
+            )
+        })?;
+        migrations.push(MigrationFile {
+            version,
+            name,
+            path,
+        });
+    }
+
+    migrations.sort_by_key(|migration| migration.version);
+    for pair in migrations.windows(2) {
+        if pair[0].version == pair[1].version {
+            return Err(Error::new(
+                span,
+                format!(
+                    "migrations {} and {} have the same version",
+                    pair[0].path.display(),
+                    pair[1].path.display()
+                ),
+            ));
+        }
+    }
+    Ok(migrations)
+}
+
+pub fn expand_macro(input: TokenStream) -> syn::Result<TokenStream> {
+    let dir = syn::parse2::<LitStr>(input)?;
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| Error::new(dir.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let migrations = read_migrations(&Path::new(&manifest_dir).join(dir.value()), dir.span())?;
+
+    let migrations = migrations.iter().map(|migration| {
+        let version = migration.version;
+        let name = &migration.name;
+        // `include_str!` makes the crate rebuild when a migration changes.
+        let path = migration.path.to_string_lossy();
+        let file_name = migration
+            .path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .unwrap_or_default();
+        quote! {
+            ::worker::Migration::new(#version, #name, ::core::include_str!(#path))
+                .with_file_name(#file_name)
+        }
+    });
+    Ok(quote! {
+        &[#(#migrations),*]
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_migration_file_names() {
+        assert_eq!(
+            parse_file_name("0001_create_users.sql"),
+            Some((1, "create_users".to_string()))
+        );
+        assert_eq!(parse_file_name("12.sql"), Some((12, String::new())));
+        assert_eq!(parse_file_name("create_users.sql"), None);
+        assert_eq!(parse_file_name("0001_create_users.txt"), None);
+    }
+}
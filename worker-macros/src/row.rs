@@ -0,0 +1,105 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Index, LitStr, Member};
+
+struct Field {
+    member: Member,
+    column: String,
+    skip: bool,
+}
+
+/// The fields of a struct, with their `#[sql(...)]` attributes.
+fn fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "only structs can be mapped to SQL rows",
+        ));
+    };
+
+    let mut fields = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let (member, mut column) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(Index::from(index)), index.to_string()),
+        };
+        let mut skip = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("sql"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    column = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"` or `skip`"))
+                }
+            })?;
+        }
+        fields.push(Field {
+            member,
+            column,
+            skip,
+        });
+    }
+    Ok(fields)
+}
+
+pub fn expand_from_row(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    let fields = fields(&input)?;
+    let named =
+        matches!(&input.data, Data::Struct(data) if matches!(data.fields, Fields::Named(_)));
+
+    let mut column_index = 0usize;
+    let inits = fields.iter().map(|field| {
+        let member = &field.member;
+        if field.skip {
+            return quote!(#member: ::core::default::Default::default());
+        }
+        let value = if named {
+            let column = &field.column;
+            quote!(row.take(#column)?)
+        } else {
+            let index = column_index;
+            quote!(row.take_index(#index)?)
+        };
+        column_index += 1;
+        quote!(#member: #value)
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::worker::FromRow for #name #ty_generics #where_clause {
+            #[allow(unused_mut, unused_variables)]
+            fn from_row(mut row: ::worker::SqlRow) -> ::worker::Result<Self> {
+                ::core::result::Result::Ok(Self { #(#inits),* })
+            }
+        }
+    })
+}
+
+pub fn expand_to_params(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    let fields = fields(&input)?;
+    let members = fields
+        .iter()
+        .filter(|field| !field.skip)
+        .map(|field| &field.member);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::worker::ToParams for #name #ty_generics #where_clause {
+            fn to_params(&self) -> ::std::vec::Vec<::worker::SqlStorageValue> {
+                ::std::vec![#(::worker::ToSqlValue::to_sql_value(&self.#members)),*]
+            }
+        }
+    })
+}
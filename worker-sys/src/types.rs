@@ -1,6 +1,7 @@
 mod ai;
 mod analytics_engine;
 mod bot_management;
+mod compression_stream;
 mod context;
 mod crypto;
 #[cfg(feature = "d1")]
@@ -9,6 +10,7 @@ mod durable_object;
 mod dynamic_dispatcher;
 mod fetcher;
 mod fixed_length_stream;
+mod html_rewriter;
 mod hyperdrive;
 mod incoming_request_cf_properties;
 #[cfg(feature = "queue")]
@@ -26,6 +28,7 @@ mod websocket_request_response_pair;
 pub use ai::*;
 pub use analytics_engine::*;
 pub use bot_management::*;
+pub use compression_stream::*;
 pub use context::*;
 pub use crypto::*;
 #[cfg(feature = "d1")]
@@ -34,6 +37,7 @@ pub use durable_object::*;
 pub use dynamic_dispatcher::*;
 pub use fetcher::*;
 pub use fixed_length_stream::*;
+pub use html_rewriter::*;
 pub use hyperdrive::*;
 pub use incoming_request_cf_properties::*;
 #[cfg(feature = "queue")]
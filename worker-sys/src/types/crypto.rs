@@ -14,3 +14,48 @@ extern "C" {
     #[wasm_bindgen(method, getter)]
     pub fn digest(this: &DigestStream) -> js_sys::Promise;
 }
+
+#[wasm_bindgen]
+extern "C" {
+    /// Bindings for the [SubtleCrypto](https://developers.cloudflare.com/workers/runtime-apis/web-crypto/#subtlecrypto-methods) methods of `crypto.subtle`
+    #[wasm_bindgen(catch, js_namespace = ["crypto", "subtle"], js_name = importKey)]
+    pub fn import_key(
+        format: &str,
+        key_data: &js_sys::Uint8Array,
+        algorithm: &JsValue,
+        extractable: bool,
+        key_usages: &js_sys::Array,
+    ) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["crypto", "subtle"])]
+    pub fn sign(
+        algorithm: &JsValue,
+        key: &JsValue,
+        data: &js_sys::Uint8Array,
+    ) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["crypto", "subtle"])]
+    pub fn verify(
+        algorithm: &JsValue,
+        key: &JsValue,
+        signature: &js_sys::Uint8Array,
+        data: &js_sys::Uint8Array,
+    ) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["crypto", "subtle"])]
+    pub fn encrypt(
+        algorithm: &JsValue,
+        key: &JsValue,
+        data: &js_sys::Uint8Array,
+    ) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = ["crypto", "subtle"])]
+    pub fn decrypt(
+        algorithm: &JsValue,
+        key: &JsValue,
+        data: &js_sys::Uint8Array,
+    ) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = crypto, js_name = getRandomValues)]
+    pub fn get_random_values(array: &js_sys::Uint8Array) -> Result<js_sys::Uint8Array, JsValue>;
+}
@@ -1,10 +1,11 @@
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{closure::ScopedClosure, prelude::*};
 
 use crate::types::DurableObjectTransaction;
 
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
     pub type DurableObjectStorage;
 
     #[wasm_bindgen(method, catch)]
@@ -56,6 +57,12 @@ extern "C" {
         closure: &Closure<dyn FnMut(DurableObjectTransaction) -> js_sys::Promise>,
     ) -> Result<js_sys::Promise, JsValue>;
 
+    #[wasm_bindgen(method, catch, js_name=transactionSync)]
+    pub fn transaction_sync(
+        this: &DurableObjectStorage,
+        closure: &ScopedClosure<dyn FnMut() -> Result<JsValue, JsValue>>,
+    ) -> Result<JsValue, JsValue>;
+
     #[wasm_bindgen(method, catch, js_name=getAlarm)]
     pub fn get_alarm(
         this: &DurableObjectStorage,
@@ -75,6 +82,29 @@ extern "C" {
         options: js_sys::Object,
     ) -> Result<js_sys::Promise, JsValue>;
 
+    #[wasm_bindgen(method, catch)]
+    pub fn sync(this: &DurableObjectStorage) -> Result<js_sys::Promise, JsValue>;
+
     #[wasm_bindgen(method, getter)]
     pub fn sql(this: &DurableObjectStorage) -> crate::types::SqlStorage;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn kv(this: &DurableObjectStorage) -> SyncKvStorage;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// The synchronous key-value API of SQLite-backed Durable Objects.
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type SyncKvStorage;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn get(this: &SyncKvStorage, key: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn put(this: &SyncKvStorage, key: &str, value: JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn delete(this: &SyncKvStorage, key: &str) -> Result<bool, JsValue>;
 }
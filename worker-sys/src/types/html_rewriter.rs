@@ -0,0 +1,208 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object, js_name=HTMLRewriter)]
+    #[derive(Debug, Clone)]
+    pub type HtmlRewriter;
+
+    #[wasm_bindgen(constructor, catch, js_class=HTMLRewriter)]
+    pub fn new() -> Result<HtmlRewriter, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_class=HTMLRewriter)]
+    pub fn on(
+        this: &HtmlRewriter,
+        selector: &str,
+        handlers: &js_sys::Object,
+    ) -> Result<HtmlRewriter, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_class=HTMLRewriter, js_name=onDocument)]
+    pub fn on_document(
+        this: &HtmlRewriter,
+        handlers: &js_sys::Object,
+    ) -> Result<HtmlRewriter, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_class=HTMLRewriter)]
+    pub fn transform(
+        this: &HtmlRewriter,
+        response: &web_sys::Response,
+    ) -> Result<web_sys::Response, JsValue>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type Element;
+
+    #[wasm_bindgen(method, getter, js_name=tagName)]
+    pub fn tag_name(this: &Element) -> String;
+
+    #[wasm_bindgen(method, setter, js_name=tagName)]
+    pub fn set_tag_name(this: &Element, name: &str);
+
+    #[wasm_bindgen(method, getter, js_name=namespaceURI)]
+    pub fn namespace_uri(this: &Element) -> String;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn attributes(this: &Element) -> js_sys::Iterator;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn removed(this: &Element) -> bool;
+
+    #[wasm_bindgen(method, js_name=getAttribute)]
+    pub fn get_attribute(this: &Element, name: &str) -> Option<String>;
+
+    #[wasm_bindgen(method, js_name=hasAttribute)]
+    pub fn has_attribute(this: &Element, name: &str) -> bool;
+
+    #[wasm_bindgen(method, catch, js_name=setAttribute)]
+    pub fn set_attribute(this: &Element, name: &str, value: &str) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method, js_name=removeAttribute)]
+    pub fn remove_attribute(this: &Element, name: &str) -> Element;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn before(this: &Element, content: &str, options: &JsValue) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn after(this: &Element, content: &str, options: &JsValue) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn prepend(this: &Element, content: &str, options: &JsValue) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn append(this: &Element, content: &str, options: &JsValue) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn replace(this: &Element, content: &str, options: &JsValue) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name=setInnerContent)]
+    pub fn set_inner_content(
+        this: &Element,
+        content: &str,
+        options: &JsValue,
+    ) -> Result<Element, JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub fn remove(this: &Element) -> Element;
+
+    #[wasm_bindgen(method, js_name=removeAndKeepContent)]
+    pub fn remove_and_keep_content(this: &Element) -> Element;
+
+    #[wasm_bindgen(method, catch, js_name=onEndTag)]
+    pub fn on_end_tag(this: &Element, handler: &js_sys::Function) -> Result<(), JsValue>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type EndTag;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn name(this: &EndTag) -> String;
+
+    #[wasm_bindgen(method, setter)]
+    pub fn set_name(this: &EndTag, name: &str);
+
+    #[wasm_bindgen(method, catch)]
+    pub fn before(this: &EndTag, content: &str, options: &JsValue) -> Result<EndTag, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn after(this: &EndTag, content: &str, options: &JsValue) -> Result<EndTag, JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub fn remove(this: &EndTag) -> EndTag;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type Comment;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn text(this: &Comment) -> String;
+
+    #[wasm_bindgen(method, setter)]
+    pub fn set_text(this: &Comment, text: &str);
+
+    #[wasm_bindgen(method, getter)]
+    pub fn removed(this: &Comment) -> bool;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn before(this: &Comment, content: &str, options: &JsValue) -> Result<Comment, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn after(this: &Comment, content: &str, options: &JsValue) -> Result<Comment, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn replace(this: &Comment, content: &str, options: &JsValue) -> Result<Comment, JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub fn remove(this: &Comment) -> Comment;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type TextChunk;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn text(this: &TextChunk) -> String;
+
+    #[wasm_bindgen(method, getter, js_name=lastInTextNode)]
+    pub fn last_in_text_node(this: &TextChunk) -> bool;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn removed(this: &TextChunk) -> bool;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn before(this: &TextChunk, content: &str, options: &JsValue)
+        -> Result<TextChunk, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn after(this: &TextChunk, content: &str, options: &JsValue) -> Result<TextChunk, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn replace(
+        this: &TextChunk,
+        content: &str,
+        options: &JsValue,
+    ) -> Result<TextChunk, JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub fn remove(this: &TextChunk) -> TextChunk;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type Doctype;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn name(this: &Doctype) -> Option<String>;
+
+    #[wasm_bindgen(method, getter, js_name=publicId)]
+    pub fn public_id(this: &Doctype) -> Option<String>;
+
+    #[wasm_bindgen(method, getter, js_name=systemId)]
+    pub fn system_id(this: &Doctype) -> Option<String>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=js_sys::Object)]
+    #[derive(Debug, Clone)]
+    pub type DocumentEnd;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn append(
+        this: &DocumentEnd,
+        content: &str,
+        options: &JsValue,
+    ) -> Result<DocumentEnd, JsValue>;
+}
@@ -1,4 +1,4 @@
-use std::ops::Deref;
+use std::{ops::Deref, time::Duration};
 
 use wasm_bindgen::JsValue;
 
@@ -66,6 +66,13 @@ impl AbortSignal {
         Self::from(web_sys::AbortSignal::abort_with_reason(&reason))
     }
 
+    /// Creates a [AbortSignal] that aborts with a `TimeoutError` once `timeout` has elapsed.
+    pub fn timeout(timeout: Duration) -> Self {
+        Self::from(web_sys::AbortSignal::timeout_with_f64(
+            timeout.as_millis() as f64
+        ))
+    }
+
     #[cfg(feature = "http")]
     pub(crate) fn inner(&self) -> &web_sys::AbortSignal {
         &self.inner
@@ -0,0 +1,368 @@
+use std::time::Duration;
+
+use futures_util::future::LocalBoxFuture;
+use futures_util::Future;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{durable::ScheduledTime, Date, Error, Result, SqlStorageValue, Storage};
+
+const TABLE: &str = "_worker_alarms";
+
+/// Multiplexes any number of named timers over the single alarm of a Durable Object.
+///
+/// Timers are persisted in the object's SQLite storage, along with a `kind` selecting the
+/// callback that handles them and a serialized payload. The alarm is always set for the earliest
+/// timer, and [`dispatch`](AlarmScheduler::dispatch) runs the callbacks of the timers that are
+/// due from [`DurableObject::alarm`](crate::DurableObject::alarm). A timer whose callback fails
+/// is retried with exponential backoff, and dropped once it has failed `max_attempts` times.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use worker::*;
+/// #[durable_object]
+/// pub struct Sessions {
+///     timers: AlarmScheduler,
+/// }
+///
+/// impl DurableObject for Sessions {
+///     fn new(state: State, _env: Env) -> Self {
+///         Self {
+///             timers: AlarmScheduler::new(state.storage()).unwrap(),
+///         }
+///     }
+///
+///     async fn fetch(&self, _req: Request) -> Result<Response> {
+///         self.timers
+///             .schedule("session:42", "expire", Duration::from_secs(3600), &42)
+///             .await?;
+///         Response::ok("ok")
+///     }
+///
+///     async fn alarm(&self) -> Result<Response> {
+///         self.timers
+///             .dispatch()
+///             .on("expire", |timer: Timer<u64>| async move {
+///                 console_log!("session {} expired", timer.payload);
+///                 Ok(())
+///             })
+///             .run()
+///             .await?;
+///         Response::ok("ok")
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AlarmScheduler {
+    storage: Storage,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+/// A due timer, as passed to its callback.
+#[derive(Debug, Clone)]
+pub struct Timer<P> {
+    /// The name the timer was scheduled with.
+    pub name: String,
+    /// The payload the timer was scheduled with.
+    pub payload: P,
+    /// When the timer was due, in milliseconds since the Unix epoch.
+    pub scheduled_at: u64,
+    /// How many times the callback already failed for this timer.
+    pub retries: u32,
+}
+
+#[derive(Deserialize)]
+struct Row {
+    id: i64,
+    name: String,
+    kind: String,
+    payload: String,
+    scheduled_at: i64,
+    retries: u32,
+}
+
+impl AlarmScheduler {
+    /// Create a scheduler storing its timers in `storage`, creating its table if needed.
+    /// Failing callbacks are retried up to 5 times, starting after 1 second.
+    pub fn new(storage: Storage) -> Result<Self> {
+        let sql = storage.sql();
+        sql.exec(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {TABLE} (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL UNIQUE,
+                    kind TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    scheduled_at INTEGER NOT NULL,
+                    retries INTEGER NOT NULL DEFAULT 0
+                )"
+            ),
+            None,
+        )?;
+        sql.exec(
+            &format!("CREATE INDEX IF NOT EXISTS {TABLE}_scheduled_at ON {TABLE} (scheduled_at)"),
+            None,
+        )?;
+        Ok(Self {
+            storage,
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(1),
+        })
+    }
+
+    /// Drop timers whose callback failed `max_attempts` times in a row.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry of a failed callback, which doubles on each retry.
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Schedule the timer `name` to be dispatched to the callback for `kind` at `time`, with
+    /// `payload`. Replaces any timer already scheduled with the same name.
+    pub async fn schedule<P: Serialize>(
+        &self,
+        name: &str,
+        kind: &str,
+        time: impl Into<ScheduledTime>,
+        payload: &P,
+    ) -> Result<()> {
+        let scheduled_at = time.into().schedule().get_time() as i64;
+        let payload = serde_json::to_string(payload)?;
+        self.storage.sql().exec(
+            &format!(
+                "INSERT OR REPLACE INTO {TABLE} (name, kind, payload, scheduled_at)
+                 VALUES (?, ?, ?, ?)"
+            ),
+            vec![
+                name.into(),
+                kind.into(),
+                payload.into(),
+                scheduled_at.into(),
+            ],
+        )?;
+        self.arm().await
+    }
+
+    /// Cancel the timer `name`. Returns whether it was scheduled.
+    pub async fn cancel(&self, name: &str) -> Result<bool> {
+        if self.scheduled_at(name)?.is_none() {
+            return Ok(false);
+        }
+        self.storage.sql().exec(
+            &format!("DELETE FROM {TABLE} WHERE name = ?"),
+            vec![name.into()],
+        )?;
+        self.arm().await?;
+        Ok(true)
+    }
+
+    /// When the timer `name` is due, in milliseconds since the Unix epoch, if it is scheduled.
+    pub fn scheduled_at(&self, name: &str) -> Result<Option<u64>> {
+        #[derive(Deserialize)]
+        struct ScheduledAt {
+            scheduled_at: i64,
+        }
+
+        let rows = self
+            .storage
+            .sql()
+            .exec(
+                &format!("SELECT scheduled_at FROM {TABLE} WHERE name = ?"),
+                vec![name.into()],
+            )?
+            .to_array::<ScheduledAt>()?;
+        Ok(rows.first().map(|row| row.scheduled_at.max(0) as u64))
+    }
+
+    /// Set the alarm for the earliest timer, or delete it if there are none.
+    async fn arm(&self) -> Result<()> {
+        #[derive(Deserialize)]
+        struct Earliest {
+            scheduled_at: Option<i64>,
+        }
+
+        let earliest = self
+            .storage
+            .sql()
+            .exec(
+                &format!("SELECT MIN(scheduled_at) AS scheduled_at FROM {TABLE}"),
+                None,
+            )?
+            .one::<Earliest>()?
+            .scheduled_at;
+        let current = self.storage.get_alarm().await?;
+        match earliest {
+            Some(time) if current != Some(time) => {
+                let date = js_sys::Date::new(&js_sys::Number::from(time as f64));
+                self.storage.set_alarm(ScheduledTime::new(date)).await
+            }
+            Some(_) => Ok(()),
+            None if current.is_some() => self.storage.delete_alarm().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Start dispatching the timers that are due to callbacks, registered by kind with
+    /// [`AlarmDispatch::on`].
+    pub fn dispatch(&self) -> AlarmDispatch<'_> {
+        AlarmDispatch {
+            scheduler: self,
+            callbacks: Vec::new(),
+        }
+    }
+}
+
+type Callback<'a> = Box<dyn Fn(&Row) -> Result<LocalBoxFuture<'a, Result<()>>> + 'a>;
+
+/// Dispatches the due timers of an [`AlarmScheduler`]. Created with
+/// [`AlarmScheduler::dispatch`].
+pub struct AlarmDispatch<'a> {
+    scheduler: &'a AlarmScheduler,
+    callbacks: Vec<(String, Callback<'a>)>,
+}
+
+impl core::fmt::Debug for AlarmDispatch<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AlarmDispatch")
+            .field(
+                "kinds",
+                &self
+                    .callbacks
+                    .iter()
+                    .map(|(kind, _)| kind)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<'a> AlarmDispatch<'a> {
+    /// Handle timers of `kind` with `callback`, which receives the timer with its payload
+    /// deserialized as `P`.
+    pub fn on<P, F, Fut>(mut self, kind: &str, callback: F) -> Self
+    where
+        P: DeserializeOwned + 'a,
+        F: Fn(Timer<P>) -> Fut + 'a,
+        Fut: Future<Output = Result<()>> + 'a,
+    {
+        let callback: Callback<'a> = Box::new(move |row: &Row| {
+            let timer = Timer {
+                name: row.name.clone(),
+                payload: serde_json::from_str(&row.payload)?,
+                scheduled_at: row.scheduled_at.max(0) as u64,
+                retries: row.retries,
+            };
+            Ok(Box::pin(callback(timer)))
+        });
+        self.callbacks.push((kind.to_string(), callback));
+        self
+    }
+
+    /// Run the callbacks of every timer that is due, then set the alarm for the next timer.
+    /// Returns how many timers were handled successfully.
+    ///
+    /// Timers without a callback for their kind fail like timers whose callback fails. Timers
+    /// whose payload can't be deserialized for their callback are dropped right away.
+    pub async fn run(self) -> Result<usize> {
+        let scheduler = self.scheduler;
+        let now = Date::now().as_millis() as i64;
+        let due = scheduler
+            .storage
+            .sql()
+            .exec(
+                &format!(
+                    "SELECT id, name, kind, payload, scheduled_at, retries FROM {TABLE}
+                     WHERE scheduled_at <= ? ORDER BY scheduled_at"
+                ),
+                vec![now.into()],
+            )?
+            .to_array::<Row>()?;
+
+        let mut handled = 0;
+        for row in &due {
+            // The timer is identified by its id rather than its name, so that a callback can
+            // schedule a new timer with the same name.
+            let sql = scheduler.storage.sql();
+            let result = match self.callbacks.iter().find(|(kind, _)| *kind == row.kind) {
+                Some((_, callback)) => match callback(row) {
+                    Ok(future) => future.await,
+                    // Retrying won't make the payload any more valid.
+                    Err(e) => {
+                        crate::console_error!(
+                            "timer {} has an invalid payload, dropping it: {e}",
+                            row.name
+                        );
+                        sql.exec(
+                            &format!("DELETE FROM {TABLE} WHERE id = ?"),
+                            vec![row.id.into()],
+                        )?;
+                        continue;
+                    }
+                },
+                None => Err(Error::RustError(format!(
+                    "no callback for timers of kind {}",
+                    row.kind
+                ))),
+            };
+
+            match result {
+                Ok(()) => {
+                    handled += 1;
+                    sql.exec(
+                        &format!("DELETE FROM {TABLE} WHERE id = ?"),
+                        vec![row.id.into()],
+                    )?;
+                }
+                Err(e) if row.retries + 1 >= scheduler.max_attempts => {
+                    crate::console_error!(
+                        "timer {} failed {} times, dropping it: {e}",
+                        row.name,
+                        row.retries + 1
+                    );
+                    sql.exec(
+                        &format!("DELETE FROM {TABLE} WHERE id = ?"),
+                        vec![row.id.into()],
+                    )?;
+                }
+                Err(e) => {
+                    crate::console_warn!("timer {} failed, retrying: {e}", row.name);
+                    let delay = retry_delay(scheduler.retry_delay, row.retries);
+                    let retry_at = Date::now().as_millis() as i64 + delay.as_millis() as i64;
+                    sql.exec(
+                        &format!(
+                            "UPDATE {TABLE} SET scheduled_at = ?, retries = retries + 1
+                             WHERE id = ?"
+                        ),
+                        vec![SqlStorageValue::from(retry_at), row.id.into()],
+                    )?;
+                }
+            }
+        }
+
+        scheduler.arm().await?;
+        Ok(handled)
+    }
+}
+
+/// The delay before retrying a timer that already failed `retries` times.
+fn retry_delay(base: Duration, retries: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(retries))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn doubles_retry_delay() {
+        let base = Duration::from_secs(1);
+        assert_eq!(retry_delay(base, 0), Duration::from_secs(1));
+        assert_eq!(retry_delay(base, 3), Duration::from_secs(8));
+        assert_eq!(retry_delay(base, 64), Duration::from_secs(u32::MAX.into()));
+    }
+}
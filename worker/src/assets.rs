@@ -0,0 +1,221 @@
+use crate::{Env, Error, Fetcher, Method, Request, RequestInit, Response, Result};
+
+/// How many redirects of the assets binding are followed when fetching a fallback page.
+const MAX_REDIRECTS: usize = 3;
+
+/// Serves the Worker's [static assets](https://developers.cloudflare.com/workers/static-assets/)
+/// from an assets binding, with an optional single-page app fallback, a custom 404 page and
+/// per-path `Cache-Control` headers.
+///
+/// Register it with [`Router::assets`](crate::Router::assets) to serve assets for every request
+/// that no route matches, so that API routes take precedence:
+///
+/// ```no_run
+/// # use worker::*;
+/// # async fn run(req: Request, env: Env) -> Result<Response> {
+/// Router::new()
+///     .get("/api/health", |_, _| Response::ok("ok"))
+///     .assets(
+///         Assets::new("ASSETS")
+///             .spa_fallback("/index.html")
+///             .cache_control("/assets/*", "public, max-age=31536000, immutable"),
+///     )
+///     .run(req, env)
+///     .await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Assets {
+    binding: String,
+    spa_fallback: Option<String>,
+    not_found_page: Option<String>,
+    cache_control: Vec<(String, String)>,
+}
+
+impl Assets {
+    /// Serve assets from the binding named `binding` in your wrangler.toml file.
+    pub fn new(binding: impl Into<String>) -> Self {
+        Self {
+            binding: binding.into(),
+            spa_fallback: None,
+            not_found_page: None,
+            cache_control: Vec::new(),
+        }
+    }
+
+    /// Answer navigations to paths that have no asset with the asset at `path`, typically
+    /// `/index.html`, so that a single-page app can route them on the client. Other requests,
+    /// such as for missing scripts or images, still receive a 404.
+    ///
+    /// With the default `html_handling` of `auto-trailing-slash`, the assets binding redirects
+    /// `/index.html` to `/`. Such redirects are followed, so the page itself is served under the
+    /// requested path.
+    pub fn spa_fallback(mut self, path: impl Into<String>) -> Self {
+        self.spa_fallback = Some(path.into());
+        self
+    }
+
+    /// Answer requests for paths that have no asset with the asset at `path` and a `404 Not
+    /// Found` status. As with [`spa_fallback`](Self::spa_fallback), redirects of the assets
+    /// binding, e.g. from `/404.html` to `/404`, are followed.
+    pub fn not_found_page(mut self, path: impl Into<String>) -> Self {
+        self.not_found_page = Some(path.into());
+        self
+    }
+
+    /// Set the `Cache-Control` header of assets whose path matches `pattern`, in which `*`
+    /// matches any sequence of characters, e.g. `/assets/*` or `*.woff2`. The first matching
+    /// pattern applies.
+    pub fn cache_control(mut self, pattern: impl Into<String>, value: impl Into<String>) -> Self {
+        self.cache_control.push((pattern.into(), value.into()));
+        self
+    }
+
+    /// Serve the asset requested by `req`.
+    pub async fn serve(&self, req: Request, env: &Env) -> Result<Response> {
+        let fetcher = env.assets(&self.binding)?;
+        let url = req.url()?;
+        let mut path = url.path().to_string();
+        let mut res = fetcher.fetch_response(req.clone()?).await?;
+
+        if res.status_code() == 404 {
+            let navigation = is_navigation(
+                &req.method(),
+                req.headers().get("Sec-Fetch-Mode")?.as_deref(),
+                req.headers().get("Accept")?.as_deref(),
+            );
+            match (&self.spa_fallback, &self.not_found_page) {
+                (Some(fallback), _) if navigation => {
+                    res = Self::fetch_page(&fetcher, &req, fallback).await?;
+                    path = fallback.clone();
+                }
+                (_, Some(page)) => {
+                    res = Self::fetch_page(&fetcher, &req, page)
+                        .await?
+                        .with_status(404);
+                    path = page.clone();
+                }
+                _ => {}
+            }
+        }
+
+        match self.cache_control_for(&path) {
+            Some(cache_control) if matches!(res.status_code(), 200 | 206 | 304) => {
+                // The headers of a fetched response are immutable.
+                let headers = res.headers().clone();
+                headers.set("Cache-Control", cache_control)?;
+                Ok(res.with_headers(headers))
+            }
+            _ => Ok(res),
+        }
+    }
+
+    /// Fetch the asset at `path` in place of the one requested by `req`, keeping its headers so
+    /// that conditional requests still apply. Redirects are followed, since the client must
+    /// receive the page under the path it requested rather than be sent to the page's own.
+    async fn fetch_page(fetcher: &Fetcher, req: &Request, path: &str) -> Result<Response> {
+        let mut url = req.url()?.join(path)?;
+        let method = match req.method() {
+            Method::Head => Method::Head,
+            _ => Method::Get,
+        };
+        for _ in 0..=MAX_REDIRECTS {
+            let mut init = RequestInit::new();
+            init.with_method(method.clone())
+                .with_headers(req.headers().clone());
+            let page = Request::new_with_init(url.as_str(), &init)?;
+            let res = fetcher.fetch_response(page).await?;
+            let location = match res.status_code() {
+                300..=399 => res.headers().get("Location")?,
+                _ => None,
+            };
+            match location {
+                Some(location) => url = url.join(&location)?,
+                None => return Ok(res),
+            }
+        }
+        Err(Error::RustError(format!(
+            "too many redirects fetching the asset at {path}"
+        )))
+    }
+
+    fn cache_control_for(&self, path: &str) -> Option<&str> {
+        self.cache_control
+            .iter()
+            .find(|(pattern, _)| glob_match(pattern, path))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Whether a request is a browser navigation, which should receive the single-page app rather
+/// than a 404. Browsers that don't send `Sec-Fetch-Mode` are recognized by asking for HTML.
+fn is_navigation(method: &Method, sec_fetch_mode: Option<&str>, accept: Option<&str>) -> bool {
+    if !matches!(method, Method::Get | Method::Head) {
+        return false;
+    }
+    match sec_fetch_mode {
+        Some(mode) => mode.eq_ignore_ascii_case("navigate"),
+        None => accept.is_some_and(|accept| accept.contains("text/html")),
+    }
+}
+
+/// Match `path` against `pattern`, in which `*` matches any sequence of characters.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let (pattern, path) = (pattern.as_bytes(), path.as_bytes());
+    let (mut p, mut s) = (0, 0);
+    // The position of the last `*` in the pattern, and of the path where it started matching.
+    let mut star = None;
+    while s < path.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, s));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == path[s] {
+            p += 1;
+            s += 1;
+        } else if let Some((star_p, star_s)) = star {
+            // Let the last `*` match one more character and retry.
+            p = star_p + 1;
+            s = star_s + 1;
+            star = Some((star_p, star_s + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("/assets/*", "/assets/app.js"));
+        assert!(glob_match("/assets/*", "/assets/img/logo.png"));
+        assert!(glob_match("*.woff2", "/fonts/inter.woff2"));
+        assert!(glob_match("/index.html", "/index.html"));
+        assert!(glob_match("/*/*.css", "/a/b/c.css"));
+        assert!(glob_match("*", "/"));
+
+        assert!(!glob_match("/assets/*", "/api/assets"));
+        assert!(!glob_match("*.woff2", "/fonts/inter.woff"));
+        assert!(!glob_match("/index.html", "/index.htm"));
+    }
+
+    #[test]
+    fn detects_navigations() {
+        assert!(is_navigation(&Method::Get, Some("navigate"), None));
+        assert!(!is_navigation(
+            &Method::Get,
+            Some("cors"),
+            Some("text/html")
+        ));
+        assert!(is_navigation(
+            &Method::Get,
+            None,
+            Some("text/html,application/xhtml+xml,*/*;q=0.8")
+        ));
+        assert!(!is_navigation(&Method::Get, None, Some("*/*")));
+        assert!(!is_navigation(&Method::Post, Some("navigate"), None));
+    }
+}
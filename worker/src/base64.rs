@@ -0,0 +1,96 @@
+//! Base64 encoding, as used by `Authorization: Basic` credentials and signed cookies.
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode `data` as padded base64 with the standard alphabet.
+pub(crate) fn encode(data: &[u8]) -> String {
+    let mut out = encode_with(STANDARD, data);
+    while out.len() % 4 != 0 {
+        out.push('=');
+    }
+    out
+}
+
+/// Decode padded base64 with the standard alphabet.
+pub(crate) fn decode(data: &str) -> Option<Vec<u8>> {
+    if data.len() % 4 != 0 {
+        return None;
+    }
+    let trimmed = data.strip_suffix("==").or(data.strip_suffix('='));
+    decode_with(STANDARD, trimmed.unwrap_or(data))
+}
+
+/// Encode `data` as unpadded base64url, which only uses characters allowed in URLs and cookie
+/// values.
+pub(crate) fn encode_url(data: &[u8]) -> String {
+    encode_with(URL_SAFE, data)
+}
+
+/// Decode unpadded base64url.
+pub(crate) fn decode_url(data: &str) -> Option<Vec<u8>> {
+    decode_with(URL_SAFE, data)
+}
+
+fn encode_with(alphabet: &[u8; 64], data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(alphabet[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+fn decode_with(alphabet: &[u8; 64], data: &str) -> Option<Vec<u8>> {
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let digit = alphabet.iter().position(|b| b == c)? as u32;
+            n |= digit << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        for len in 0..8 {
+            let data = (0..len).map(|i| (i * 73 + 250) as u8).collect::<Vec<_>>();
+            assert_eq!(decode(&encode(&data)), Some(data.clone()));
+            assert_eq!(decode_url(&encode_url(&data)), Some(data));
+        }
+    }
+
+    #[test]
+    fn encodes_known_values() {
+        assert_eq!(encode(b"hello?"), "aGVsbG8/");
+        assert_eq!(encode(b"user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(encode(b"ab"), "YWI=");
+        assert_eq!(encode_url(b"hello?"), "aGVsbG8_");
+        assert_eq!(encode_url(b"ab"), "YWI");
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(decode("YWI"), None);
+        assert_eq!(decode("a*=="), None);
+        assert_eq!(decode_url("a"), None);
+        assert_eq!(decode_url("a+=="), None);
+    }
+}
@@ -0,0 +1,371 @@
+use std::{ops::Deref, time::Duration};
+
+use js_sys::Uint8Array;
+use serde::Serialize;
+use url::Url;
+
+use crate::{
+    date::parse_http_date, AbortSignal, Date, Delay, Fetcher, Headers, Method, Request,
+    RequestInit, Response, ResponseBody, Result,
+};
+
+/// When and how often a [`Client`] retries a request.
+///
+/// Retries wait with exponential backoff and full jitter: the `n`th retry waits a random
+/// duration up to `base_delay * 2^n`, capped at `max_delay`. A `Retry-After` header on the
+/// response is honored instead, unless it asks to wait longer than `max_delay`, in which case
+/// the response is returned as-is.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Retry up to `max_retries` times on network errors, timeouts and `408`, `429`, `500`,
+    /// `502`, `503` and `504` responses.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            statuses: vec![408, 429, 500, 502, 503, 504],
+        }
+    }
+
+    /// Never retry.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Set the delay the backoff starts from. Defaults to 100ms.
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the longest delay between two attempts. Defaults to 10s.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Set the response statuses that are retried.
+    pub fn with_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// The delay before retry number `retry` (starting at 0), given a `random` number in
+    /// `[0, 1)`.
+    fn backoff(&self, retry: u32, random: f64) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        ceiling.mul_f64(random)
+    }
+}
+
+/// Parse a `Retry-After` header, either a number of seconds or an HTTP date, relative to `now`
+/// in seconds since the Unix epoch.
+fn parse_retry_after(value: &str, now: u64) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = parse_http_date(value)?;
+    Some(Duration::from_secs(date.saturating_sub(now)))
+}
+
+/// An HTTP client for outbound requests, made with the global `fetch` or through a service
+/// binding's [`Fetcher`], with a base URL, default headers, timeouts and retries.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use worker::*;
+/// # async fn run(env: Env) -> Result<()> {
+/// let client = Client::new()
+///     .with_base_url(Url::parse("https://api.example.com/v1/")?)
+///     .with_header("Authorization", "Bearer token")?
+///     .with_timeout(Duration::from_secs(5))
+///     .with_retry(RetryPolicy::new(3));
+///
+/// let mut res = client.get("users/42").send().await?;
+/// let user: serde_json::Value = res.json().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Client {
+    fetcher: Option<Fetcher>,
+    base_url: Option<Url>,
+    headers: Headers,
+    timeout: Option<Duration>,
+    retry: RetryPolicy,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// A client making requests with the global `fetch`. Requests are not retried by default.
+    pub fn new() -> Self {
+        Self {
+            fetcher: None,
+            base_url: None,
+            headers: Headers::new(),
+            timeout: None,
+            retry: RetryPolicy::none(),
+        }
+    }
+
+    /// A client making requests through a service binding or any other [`Fetcher`].
+    pub fn with_fetcher(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher: Some(fetcher),
+            ..Self::new()
+        }
+    }
+
+    /// Resolve request URLs against `base_url`. Include a trailing slash for relative paths to
+    /// be resolved below it.
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Send a header with every request.
+    pub fn with_header(self, name: &str, value: &str) -> Result<Self> {
+        self.headers.set(name, value)?;
+        Ok(self)
+    }
+
+    /// Abort each attempt that takes longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry requests according to `retry`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Start building a request for `url`, which is resolved against the base URL if any.
+    pub fn request(&self, method: Method, url: &str) -> ClientRequest<'_> {
+        let url = match &self.base_url {
+            Some(base_url) => base_url.join(url),
+            None => Url::parse(url),
+        };
+        ClientRequest {
+            client: self,
+            method,
+            url,
+            headers: self.headers.clone(),
+            body: None,
+            timeout: self.timeout,
+            retry: self.retry.clone(),
+            idempotent: None,
+        }
+    }
+
+    pub fn get(&self, url: &str) -> ClientRequest<'_> {
+        self.request(Method::Get, url)
+    }
+
+    pub fn head(&self, url: &str) -> ClientRequest<'_> {
+        self.request(Method::Head, url)
+    }
+
+    pub fn post(&self, url: &str) -> ClientRequest<'_> {
+        self.request(Method::Post, url)
+    }
+
+    pub fn put(&self, url: &str) -> ClientRequest<'_> {
+        self.request(Method::Put, url)
+    }
+
+    pub fn patch(&self, url: &str) -> ClientRequest<'_> {
+        self.request(Method::Patch, url)
+    }
+
+    pub fn delete(&self, url: &str) -> ClientRequest<'_> {
+        self.request(Method::Delete, url)
+    }
+
+    async fn fetch(&self, request: Request) -> Result<Response> {
+        match &self.fetcher {
+            Some(fetcher) => fetcher.fetch_response(request).await,
+            None => crate::global::fetch(request).await,
+        }
+    }
+}
+
+/// A request built by a [`Client`]. Call [`send`](ClientRequest::send) to make it.
+#[derive(Debug)]
+pub struct ClientRequest<'a> {
+    client: &'a Client,
+    method: Method,
+    url: std::result::Result<Url, url::ParseError>,
+    headers: Headers,
+    body: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    retry: RetryPolicy,
+    idempotent: Option<bool>,
+}
+
+impl ClientRequest<'_> {
+    /// Set a header, replacing the client's default for the same name.
+    pub fn with_header(self, name: &str, value: &str) -> Result<Self> {
+        self.headers.set(name, value)?;
+        Ok(self)
+    }
+
+    /// Set the body. It is kept in memory so that it can be sent again on retries.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set the body to `value` serialized as JSON, along with the `Content-Type` header.
+    pub fn with_json<T: Serialize>(self, value: &T) -> Result<Self> {
+        let body = serde_json::to_vec(value)?;
+        Ok(self
+            .with_header("Content-Type", "application/json")?
+            .with_body(body))
+    }
+
+    /// Abort each attempt that takes longer than `timeout`, instead of the client's timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry this request according to `retry`, instead of the client's policy.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Mark this request as safe, or not, to send more than once. By default only requests with
+    /// an [idempotent method](Method::is_idempotent) or an `Idempotency-Key` header are retried.
+    pub fn with_idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = Some(idempotent);
+        self
+    }
+
+    /// Send the request, retrying it as configured. Attempts that time out fail with the
+    /// `TimeoutError` raised by the runtime.
+    pub async fn send(self) -> Result<Response> {
+        let url = self.url.clone()?;
+        let idempotent = match self.idempotent {
+            Some(idempotent) => idempotent,
+            None => self.method.is_idempotent() || self.headers.has("Idempotency-Key")?,
+        };
+
+        let mut retry = 0;
+        loop {
+            let result = self.attempt(&url).await;
+            if !idempotent || retry >= self.retry.max_retries {
+                return result;
+            }
+
+            let backoff = self.retry.backoff(retry, js_sys::Math::random());
+            let delay = match &result {
+                Ok(res) if self.retry.statuses.contains(&res.status_code()) => {
+                    let now = Date::now().as_millis() / 1000;
+                    let retry_after = res
+                        .headers()
+                        .get("Retry-After")?
+                        .and_then(|value| parse_retry_after(&value, now));
+                    match retry_after {
+                        Some(delay) if delay > self.retry.max_delay => return result,
+                        Some(delay) => delay,
+                        None => backoff,
+                    }
+                }
+                Ok(_) => return result,
+                Err(_) => backoff,
+            };
+
+            // Release the connection held by the failed attempt's unread body.
+            if let Ok(res) = &result {
+                if let ResponseBody::Stream(stream) = res.body() {
+                    let _ = stream.cancel();
+                }
+            }
+            Delay::from(delay).await;
+            retry += 1;
+        }
+    }
+
+    async fn attempt(&self, url: &Url) -> Result<Response> {
+        let mut init = RequestInit::new();
+        init.with_method(self.method.clone())
+            .with_headers(self.headers.clone());
+        if let Some(body) = &self.body {
+            init.with_body(Some(Uint8Array::from(body.as_slice()).into()));
+        }
+        let init = web_sys::RequestInit::from(&init);
+        if let Some(timeout) = self.timeout {
+            init.set_signal(Some(AbortSignal::timeout(timeout).deref()));
+        }
+        let request = web_sys::Request::new_with_str_and_init(url.as_str(), &init)?;
+        self.client.fetch(request.into()).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially_up_to_the_max_delay() {
+        let policy = RetryPolicy::new(5)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1));
+        assert_eq!(policy.backoff(0, 1.0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2, 1.0), Duration::from_millis(400));
+        assert_eq!(policy.backoff(2, 0.5), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40, 1.0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3, 0.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn parses_retry_after() {
+        assert_eq!(parse_retry_after("120", 0), Some(Duration::from_secs(120)));
+        // Sun, 06 Nov 1994 08:49:37 GMT
+        let date = 784111777;
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", date - 30),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", date + 30),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", 0), None);
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        assert!(Method::Get.is_idempotent());
+        assert!(Method::Put.is_idempotent());
+        assert!(!Method::Post.is_idempotent());
+        assert!(!Method::Patch.is_idempotent());
+    }
+}
@@ -0,0 +1,274 @@
+use futures_util::StreamExt;
+use js_sys::Uint8Array;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{ReadableStream, ReadableWritablePair, TransformStream};
+use worker_sys::{CompressionStream, DecompressionStream};
+
+use crate::{
+    headers::Vary, ByteStream, EncodeBody, Error, Request, Response, ResponseBody, Result,
+};
+
+/// A compression format supported by the runtime's `CompressionStream` and
+/// `DecompressionStream`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    /// gzip, the `gzip` content coding.
+    Gzip,
+    /// zlib-wrapped DEFLATE, the `deflate` content coding.
+    Deflate,
+    /// DEFLATE without any wrapper. It has no HTTP content coding, so it can only be used on
+    /// streams.
+    DeflateRaw,
+}
+
+impl CompressionFormat {
+    /// The name of the format as understood by `CompressionStream`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionFormat::Gzip => "gzip",
+            CompressionFormat::Deflate => "deflate",
+            CompressionFormat::DeflateRaw => "deflate-raw",
+        }
+    }
+
+    /// The value of the `Content-Encoding` header for a body in this format.
+    pub fn content_coding(&self) -> Option<&'static str> {
+        match self {
+            CompressionFormat::Gzip => Some("gzip"),
+            CompressionFormat::Deflate => Some("deflate"),
+            CompressionFormat::DeflateRaw => None,
+        }
+    }
+
+    /// The format of a body sent with `Content-Encoding: coding`. Returns `Ok(None)` for the
+    /// `identity` coding, and an error for codings that can't be decompressed.
+    pub fn from_content_coding(coding: &str) -> Result<Option<Self>> {
+        match coding.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => Ok(None),
+            "gzip" | "x-gzip" => Ok(Some(CompressionFormat::Gzip)),
+            "deflate" => Ok(Some(CompressionFormat::Deflate)),
+            coding => Err(Error::RustError(format!(
+                "unsupported content encoding: {coding}"
+            ))),
+        }
+    }
+
+    /// The format to compress a response in, given the request's `Accept-Encoding` header.
+    /// gzip is preferred over deflate when the client accepts both equally.
+    pub fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let mut gzip = None;
+        let mut deflate = None;
+        let mut any = None;
+        for coding in accept_encoding.split(',') {
+            let mut params = coding.split(';');
+            let name = params
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            match name.as_str() {
+                "gzip" | "x-gzip" => gzip = Some(quality),
+                "deflate" => deflate = Some(quality),
+                "*" => any = Some(quality),
+                _ => {}
+            }
+        }
+
+        let gzip = gzip.or(any).unwrap_or(0.0);
+        let deflate = deflate.or(any).unwrap_or(0.0);
+        if gzip > 0.0 && gzip >= deflate {
+            Some(CompressionFormat::Gzip)
+        } else if deflate > 0.0 {
+            Some(CompressionFormat::Deflate)
+        } else {
+            None
+        }
+    }
+}
+
+/// Pipe `stream` through `transform`. Unlike a bare `pipeTo`, `pipeThrough` marks the promise
+/// of the pipe as handled, so a source that errors doesn't cause an unhandled rejection.
+fn pipe_through(stream: &ReadableStream, transform: &TransformStream) -> ReadableStream {
+    let pair = ReadableWritablePair::new(&transform.readable(), &transform.writable());
+    stream.pipe_through(&pair)
+}
+
+/// Compress `stream` as it is read.
+fn compress(stream: &ReadableStream, format: CompressionFormat) -> Result<ReadableStream> {
+    let transform = CompressionStream::new(format.as_str())?;
+    Ok(pipe_through(stream, &transform))
+}
+
+/// Decompress `stream` as it is read.
+fn decompress(stream: &ReadableStream, format: CompressionFormat) -> Result<ReadableStream> {
+    let transform = DecompressionStream::new(format.as_str())?;
+    Ok(pipe_through(stream, &transform))
+}
+
+fn into_readable_stream(stream: ByteStream) -> ReadableStream {
+    let stream = stream.map(|chunk| {
+        chunk
+            .map(|chunk| Uint8Array::from(chunk.as_slice()).into())
+            .map_err(JsValue::from)
+    });
+    wasm_streams::ReadableStream::from_stream(stream)
+        .into_raw()
+        .unchecked_into()
+}
+
+impl ByteStream {
+    /// Compress this stream as it is read.
+    pub fn compress(self, format: CompressionFormat) -> Result<ByteStream> {
+        Ok(compress(&into_readable_stream(self), format)?.into())
+    }
+
+    /// Decompress this stream as it is read.
+    pub fn decompress(self, format: CompressionFormat) -> Result<ByteStream> {
+        Ok(decompress(&into_readable_stream(self), format)?.into())
+    }
+}
+
+fn body_stream(body: ResponseBody) -> Result<Option<ReadableStream>> {
+    Ok(match body {
+        ResponseBody::Empty => None,
+        ResponseBody::Stream(stream) => Some(stream),
+        ResponseBody::Body(bytes) => {
+            let array = Uint8Array::from(bytes.as_slice());
+            web_sys::Response::new_with_opt_buffer_source(Some(&array))?.body()
+        }
+    })
+}
+
+impl Response {
+    /// Compress this response's body in the format preferred by `req`'s `Accept-Encoding`
+    /// header, as it is streamed to the client.
+    ///
+    /// The response is returned unchanged if it has no body, already has a `Content-Encoding`,
+    /// or the client accepts neither gzip nor deflate. `Vary: Accept-Encoding` is added whenever
+    /// the body could have been compressed.
+    pub fn compress(self, req: &Request) -> Result<Response> {
+        if matches!(self.body(), ResponseBody::Empty)
+            || matches!(self.status_code(), 204 | 304)
+            || self.headers().has("Content-Encoding")?
+        {
+            return Ok(self);
+        }
+
+        // A copy, since the headers of a fetched response are immutable.
+        let headers = self.headers().clone();
+        let varies = headers.header::<Vary>().ok().flatten();
+        if !varies.is_some_and(|vary| vary.contains("Accept-Encoding")) {
+            headers.append("Vary", "Accept-Encoding")?;
+        }
+        let response = self.with_headers(headers);
+        let format = req
+            .headers()
+            .get("Accept-Encoding")?
+            .and_then(|accept_encoding| CompressionFormat::negotiate(&accept_encoding));
+        match format {
+            Some(format) => response.compress_with(format),
+            None => Ok(response),
+        }
+    }
+
+    /// Compress this response's body in `format`, as it is streamed to the client, setting
+    /// `Content-Encoding` accordingly. Fails for [`CompressionFormat::DeflateRaw`], which has
+    /// no HTTP content coding.
+    pub fn compress_with(self, format: CompressionFormat) -> Result<Response> {
+        let coding = format.content_coding().ok_or_else(|| {
+            Error::RustError(format!("{} has no HTTP content coding", format.as_str()))
+        })?;
+
+        let headers = self.headers().clone();
+        headers.set("Content-Encoding", coding)?;
+        headers.delete("Content-Length")?;
+        let (builder, body) = self.into_parts();
+        let builder = builder
+            .with_headers(headers)
+            // The body is already encoded, so it must not be compressed again by the runtime.
+            .with_encode_body(EncodeBody::Manual);
+        Ok(match body_stream(body)? {
+            Some(stream) => builder.stream(compress(&stream, format)?),
+            None => builder.empty(),
+        })
+    }
+
+    /// Decompress this response's body according to its `Content-Encoding` header, removing
+    /// that header. Useful for fetched responses that are received still encoded, such as those
+    /// fetched with `encodeResponseBody: "manual"`.
+    pub fn decompress(self) -> Result<Response> {
+        let Some(coding) = self.headers().get("Content-Encoding")? else {
+            return Ok(self);
+        };
+        let Some(format) = CompressionFormat::from_content_coding(&coding)? else {
+            return Ok(self);
+        };
+
+        let headers = self.headers().clone();
+        headers.delete("Content-Encoding")?;
+        headers.delete("Content-Length")?;
+        let (builder, body) = self.into_parts();
+        let builder = builder.with_headers(headers);
+        Ok(match body_stream(body)? {
+            Some(stream) => builder.stream(decompress(&stream, format)?),
+            None => builder.empty(),
+        })
+    }
+}
+
+impl Request {
+    /// Access this request's body as a [`Stream`](futures::stream::Stream) of bytes,
+    /// decompressed according to its `Content-Encoding` header. Fails if the body is encoded
+    /// with a coding other than gzip or deflate.
+    pub fn decompressed_stream(&mut self) -> Result<ByteStream> {
+        let format = match self.headers().get("Content-Encoding")? {
+            Some(coding) => CompressionFormat::from_content_coding(&coding)?,
+            None => None,
+        };
+        let stream = self.stream()?;
+        match format {
+            Some(format) => stream.decompress(format),
+            None => Ok(stream),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negotiates_content_coding() {
+        use CompressionFormat::*;
+        assert_eq!(
+            CompressionFormat::negotiate("gzip, deflate, br"),
+            Some(Gzip)
+        );
+        assert_eq!(
+            CompressionFormat::negotiate("deflate, gzip;q=0.5"),
+            Some(Deflate)
+        );
+        assert_eq!(CompressionFormat::negotiate("br, *;q=0.1"), Some(Gzip));
+        assert_eq!(CompressionFormat::negotiate("*, gzip;q=0"), Some(Deflate));
+        assert_eq!(CompressionFormat::negotiate("br, identity"), None);
+        assert_eq!(CompressionFormat::negotiate("gzip;q=0, deflate;q=0"), None);
+        assert_eq!(CompressionFormat::negotiate(""), None);
+    }
+
+    #[test]
+    fn parses_content_coding() {
+        assert_eq!(
+            CompressionFormat::from_content_coding("GZIP").unwrap(),
+            Some(CompressionFormat::Gzip)
+        );
+        assert_eq!(
+            CompressionFormat::from_content_coding("identity").unwrap(),
+            None
+        );
+        assert!(CompressionFormat::from_content_coding("br").is_err());
+    }
+}
@@ -0,0 +1,523 @@
+use std::time::Duration;
+
+use js_sys::{
+    futures::JsFuture, Array, ArrayBuffer, JsString, Object as JsObject, Reflect, Uint8Array,
+};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::{base64, date::http_date, Date, Error, Result, Secret};
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    /// Sends the cookie with cross-site requests. Browsers only accept this on `Secure` cookies,
+    /// so the attribute is always added.
+    None,
+}
+
+/// A cookie to set with [`ResponseBuilder::with_cookie`](crate::ResponseBuilder::with_cookie).
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use worker::*;
+/// # fn main() -> Result<()> {
+/// let cookie = Cookie::new("session", "abc123")
+///     .path("/")
+///     .max_age(Duration::from_secs(3600))
+///     .http_only(true)
+///     .secure(true)
+///     .same_site(SameSite::Lax);
+/// let res = Response::builder().with_cookie(&cookie)?.ok("Logged in");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    expires: Option<u64>,
+    max_age: Option<Duration>,
+    domain: Option<String>,
+    path: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+    partitioned: bool,
+}
+
+impl Cookie {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            expires: None,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+            partitioned: false,
+        }
+    }
+
+    /// A cookie that removes the cookie named `name` from the client. The `Path` and `Domain`
+    /// must match the ones the cookie was set with.
+    pub fn removal(name: impl Into<String>) -> Self {
+        let mut cookie = Self::new(name, "").max_age(Duration::ZERO);
+        cookie.expires = Some(0);
+        cookie
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Set the date after which the client deletes the cookie. [`max_age`](Cookie::max_age)
+    /// takes precedence where both are set.
+    pub fn expires(mut self, expires: Date) -> Self {
+        self.expires = Some(expires.as_millis() / 1000);
+        self
+    }
+
+    /// Set how long the client keeps the cookie.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Only send the cookie over HTTPS. Implied by [`SameSite::None`], by
+    /// [`partitioned`](Cookie::partitioned) and by the `__Secure-` and `__Host-` name prefixes.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Hide the cookie from JavaScript.
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Store the cookie separately for each top-level site it is embedded in (CHIPS).
+    pub fn partitioned(mut self, partitioned: bool) -> Self {
+        self.partitioned = partitioned;
+        self
+    }
+
+    /// The value of the `Set-Cookie` header setting this cookie. Errors if the name, value, path
+    /// or domain contain characters that aren't allowed in a cookie, or if a `__Host-` cookie
+    /// has a `Domain` or a `Path` other than `/`.
+    pub fn to_header(&self) -> Result<String> {
+        if self.name.is_empty() || !self.name.bytes().all(is_tchar) {
+            return Err(Error::RustError(format!(
+                "invalid cookie name `{}`",
+                self.name
+            )));
+        }
+        let value = self
+            .value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(&self.value);
+        if !value.bytes().all(is_cookie_octet) {
+            return Err(Error::RustError(format!(
+                "invalid value for cookie `{}`",
+                self.name
+            )));
+        }
+        for (attribute, value) in [("Domain", &self.domain), ("Path", &self.path)] {
+            if let Some(value) = value {
+                if value.bytes().any(|b| b == b';' || b.is_ascii_control()) {
+                    return Err(Error::RustError(format!(
+                        "invalid {attribute} for cookie `{}`",
+                        self.name
+                    )));
+                }
+            }
+        }
+        if self.name.starts_with("__Host-")
+            && (self.domain.is_some() || self.path.as_deref() != Some("/"))
+        {
+            return Err(Error::RustError(format!(
+                "cookie `{}` must have a Path of `/` and no Domain",
+                self.name
+            )));
+        }
+
+        let mut header = format!("{}={}", self.name, self.value);
+        if let Some(expires) = self.expires {
+            header.push_str("; Expires=");
+            header.push_str(&http_date(expires));
+        }
+        if let Some(max_age) = self.max_age {
+            header.push_str(&format!("; Max-Age={}", max_age.as_secs()));
+        }
+        if let Some(domain) = &self.domain {
+            header.push_str("; Domain=");
+            header.push_str(domain);
+        }
+        if let Some(path) = &self.path {
+            header.push_str("; Path=");
+            header.push_str(path);
+        }
+        let secure = self.secure
+            || self.partitioned
+            || self.same_site == Some(SameSite::None)
+            || self.name.starts_with("__Secure-")
+            || self.name.starts_with("__Host-");
+        if secure {
+            header.push_str("; Secure");
+        }
+        if self.http_only {
+            header.push_str("; HttpOnly");
+        }
+        if let Some(same_site) = self.same_site {
+            header.push_str(match same_site {
+                SameSite::Strict => "; SameSite=Strict",
+                SameSite::Lax => "; SameSite=Lax",
+                SameSite::None => "; SameSite=None",
+            });
+        }
+        if self.partitioned {
+            header.push_str("; Partitioned");
+        }
+        Ok(header)
+    }
+}
+
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+fn is_cookie_octet(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+/// The cookies sent with a request, parsed from its `Cookie` header by
+/// [`Request::cookies`](crate::Request::cookies).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieJar {
+    cookies: Vec<(String, String)>,
+}
+
+impl CookieJar {
+    pub(crate) fn parse(header: &str) -> Self {
+        let cookies = header
+            .split(';')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                let name = name.trim();
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                (!name.is_empty()).then(|| (name.to_string(), value.to_string()))
+            })
+            .collect();
+        Self { cookies }
+    }
+
+    /// The value of the cookie named `name`. If the client sent several cookies with that name,
+    /// the first one is returned.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of the cookie named `name`, if it was signed with
+    /// [`CookieKey::sign`] using `key`. Cookies with a missing or invalid signature are treated
+    /// as absent.
+    pub async fn get_signed(&self, name: &str, key: &CookieKey) -> Result<Option<String>> {
+        match self.get(name) {
+            Some(value) => key.verify(name, value).await,
+            None => Ok(None),
+        }
+    }
+
+    /// The decrypted value of the cookie named `name`, if it was encrypted with
+    /// [`CookieKey::encrypt`] using `key`. Cookies that fail to decrypt are treated as absent.
+    pub async fn get_encrypted(&self, name: &str, key: &CookieKey) -> Result<Option<String>> {
+        match self.get(name) {
+            Some(value) => key.decrypt(name, value).await,
+            None => Ok(None),
+        }
+    }
+
+    /// The name and value of every cookie, in the order the client sent them.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.cookies.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+const SIGNING_CONTEXT: &[u8] = b"worker cookie signing";
+const ENCRYPTION_CONTEXT: &[u8] = b"worker cookie encryption";
+const NONCE_LEN: usize = 12;
+
+/// A key for signing and encrypting cookies with Web Crypto.
+///
+/// Signed cookies carry an HMAC-SHA256 tag over their name and value, so that the client can
+/// read but not alter them. Encrypted cookies are sealed with AES-256-GCM, so that the client can
+/// neither read nor alter them. Separate keys for both are derived from the secret.
+///
+/// ```no_run
+/// # use worker::*;
+/// # async fn handler(req: Request, env: Env) -> Result<Response> {
+/// let key = CookieKey::from_secret(&env.secret("COOKIE_SECRET")?);
+/// if let Some(user) = req.cookies()?.get_signed("user", &key).await? {
+///     return Response::ok(format!("Hello, {user}"));
+/// }
+/// let cookie = key.sign(Cookie::new("user", "alice").http_only(true)).await?;
+/// Response::builder().with_cookie(&cookie)?.ok("Hello, stranger")
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CookieKey {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for CookieKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieKey").finish_non_exhaustive()
+    }
+}
+
+impl CookieKey {
+    /// Use `secret` as the key. It should be at least 32 random bytes.
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+
+    /// Use the value of a [`Secret`] binding as the key.
+    pub fn from_secret(secret: &Secret) -> Self {
+        Self::new(secret.to_string())
+    }
+
+    /// Sign the value of `cookie`.
+    pub async fn sign(&self, cookie: Cookie) -> Result<Cookie> {
+        let key = hmac_key(&self.derive(SIGNING_CONTEXT).await?, "sign").await?;
+        let tag = await_bytes(worker_sys::sign(
+            &hmac_algorithm(),
+            &key,
+            &Uint8Array::from(signed_message(&cookie.name, &cookie.value).as_slice()),
+        )?)
+        .await?;
+        let value = format!("{}.{}", cookie.value, base64::encode_url(&tag));
+        Ok(Cookie { value, ..cookie })
+    }
+
+    /// Encrypt the value of `cookie`.
+    pub async fn encrypt(&self, cookie: Cookie) -> Result<Cookie> {
+        let key = self.aes_key("encrypt").await?;
+        let nonce = Uint8Array::new_with_length(NONCE_LEN as u32);
+        worker_sys::get_random_values(&nonce)?;
+        let sealed = await_bytes(worker_sys::encrypt(
+            &aes_algorithm(&nonce, &cookie.name),
+            &key,
+            &Uint8Array::from(cookie.value.as_bytes()),
+        )?)
+        .await?;
+
+        let mut data = nonce.to_vec();
+        data.extend(sealed);
+        let value = base64::encode_url(&data);
+        Ok(Cookie { value, ..cookie })
+    }
+
+    async fn verify(&self, name: &str, value: &str) -> Result<Option<String>> {
+        let Some((value, tag)) = value.rsplit_once('.') else {
+            return Ok(None);
+        };
+        let Some(tag) = base64::decode_url(tag) else {
+            return Ok(None);
+        };
+        let key = hmac_key(&self.derive(SIGNING_CONTEXT).await?, "verify").await?;
+        let valid = JsFuture::from(worker_sys::verify(
+            &hmac_algorithm(),
+            &key,
+            &Uint8Array::from(tag.as_slice()),
+            &Uint8Array::from(signed_message(name, value).as_slice()),
+        )?)
+        .await?;
+        Ok(valid.is_truthy().then(|| value.to_string()))
+    }
+
+    async fn decrypt(&self, name: &str, value: &str) -> Result<Option<String>> {
+        let data = match base64::decode_url(value) {
+            Some(data) if data.len() > NONCE_LEN => data,
+            _ => return Ok(None),
+        };
+        let (nonce, sealed) = data.split_at(NONCE_LEN);
+        let key = self.aes_key("decrypt").await?;
+        let opened = match worker_sys::decrypt(
+            &aes_algorithm(&Uint8Array::from(nonce), name),
+            &key,
+            &Uint8Array::from(sealed),
+        ) {
+            Ok(promise) => await_bytes(promise).await,
+            Err(e) => Err(e.into()),
+        };
+        // Decryption fails with an `OperationError` when the data was tampered with.
+        Ok(opened.ok().and_then(|bytes| String::from_utf8(bytes).ok()))
+    }
+
+    /// Derive a sub-key for `context` by computing an HMAC of it with the secret.
+    async fn derive(&self, context: &[u8]) -> Result<Vec<u8>> {
+        let key = hmac_key(&self.secret, "sign").await?;
+        await_bytes(worker_sys::sign(
+            &hmac_algorithm(),
+            &key,
+            &Uint8Array::from(context),
+        )?)
+        .await
+    }
+
+    async fn aes_key(&self, usage: &str) -> Result<JsValue> {
+        let raw = self.derive(ENCRYPTION_CONTEXT).await?;
+        let algorithm = object(&[("name", "AES-GCM".into())]);
+        import_key(&raw, &algorithm, usage).await
+    }
+}
+
+/// The message authenticated by the signature of a cookie: its name and value, separated by a
+/// character that can't appear in a name so that the split between them is unambiguous.
+fn signed_message(name: &str, value: &str) -> Vec<u8> {
+    format!("{name}={value}").into_bytes()
+}
+
+fn object(properties: &[(&str, JsValue)]) -> JsValue {
+    let object = JsObject::new();
+    for (key, value) in properties {
+        let res = Reflect::set(&object, &JsString::from(*key), value);
+        debug_assert!(
+            res.is_ok(),
+            "setting properties should never fail on a plain object"
+        );
+    }
+    object.into()
+}
+
+fn hmac_algorithm() -> JsValue {
+    object(&[("name", "HMAC".into()), ("hash", "SHA-256".into())])
+}
+
+fn aes_algorithm(nonce: &Uint8Array, name: &str) -> JsValue {
+    object(&[
+        ("name", "AES-GCM".into()),
+        ("iv", nonce.into()),
+        ("additionalData", Uint8Array::from(name.as_bytes()).into()),
+    ])
+}
+
+async fn hmac_key(raw: &[u8], usage: &str) -> Result<JsValue> {
+    import_key(raw, &hmac_algorithm(), usage).await
+}
+
+async fn import_key(raw: &[u8], algorithm: &JsValue, usage: &str) -> Result<JsValue> {
+    let usages = Array::of1(&usage.into());
+    let promise = worker_sys::import_key("raw", &Uint8Array::from(raw), algorithm, false, &usages)?;
+    Ok(JsFuture::from(promise).await?)
+}
+
+async fn await_bytes(promise: js_sys::Promise) -> Result<Vec<u8>> {
+    let buffer: ArrayBuffer = JsFuture::from(promise).await?.unchecked_into();
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_set_cookie_headers() {
+        let cookie = Cookie::new("session", "abc123")
+            .domain("example.com")
+            .path("/")
+            .max_age(Duration::from_secs(3600))
+            .http_only(true)
+            .same_site(SameSite::Lax);
+        assert_eq!(
+            cookie.to_header().unwrap(),
+            "session=abc123; Max-Age=3600; Domain=example.com; Path=/; HttpOnly; SameSite=Lax"
+        );
+
+        let cookie = Cookie::new("embed", "1")
+            .same_site(SameSite::None)
+            .partitioned(true);
+        assert_eq!(
+            cookie.to_header().unwrap(),
+            "embed=1; Secure; SameSite=None; Partitioned"
+        );
+
+        assert_eq!(
+            Cookie::removal("session").path("/").to_header().unwrap(),
+            "session=; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0; Path=/"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_cookies() {
+        assert!(Cookie::new("", "v").to_header().is_err());
+        assert!(Cookie::new("a b", "v").to_header().is_err());
+        assert!(Cookie::new("a", "x;y").to_header().is_err());
+        assert!(Cookie::new("a", "x y").to_header().is_err());
+        assert!(Cookie::new("a", "v")
+            .path("/; Domain=evil")
+            .to_header()
+            .is_err());
+        assert!(Cookie::new("__Host-id", "v").to_header().is_err());
+        assert_eq!(
+            Cookie::new("__Host-id", "v").path("/").to_header().unwrap(),
+            "__Host-id=v; Path=/; Secure"
+        );
+        assert!(Cookie::new("a", "\"quoted\"").to_header().is_ok());
+    }
+
+    #[test]
+    fn parses_cookie_header() {
+        let jar = CookieJar::parse("a=1; b=\"two\";c=; =bad; novalue; a=3");
+        assert_eq!(jar.get("a"), Some("1"));
+        assert_eq!(jar.get("b"), Some("two"));
+        assert_eq!(jar.get("c"), Some(""));
+        assert_eq!(jar.get("novalue"), None);
+        assert_eq!(
+            jar.iter().collect::<Vec<_>>(),
+            [("a", "1"), ("b", "two"), ("c", ""), ("a", "3")]
+        );
+        assert!(CookieJar::parse("").is_empty());
+    }
+}
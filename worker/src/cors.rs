@@ -1,4 +1,4 @@
-use crate::{Error, Headers, Method, Result};
+use crate::{Error, Headers, Method, Middleware, Request, Response, Result, RouteContext};
 
 /// Cors struct, holding cors configuration
 #[derive(Debug, Clone)]
@@ -116,6 +116,37 @@ impl Cors {
         }
         Ok(())
     }
+
+    /// Builds the response to a preflight request for a path that can be requested with
+    /// `allowed`, which is advertised when this policy doesn't list methods of its own.
+    pub(crate) fn preflight_response(&self, allowed: &[Method]) -> Result<Response> {
+        let res = Response::empty()?.with_status(204);
+        if self.methods.is_empty() {
+            res.with_cors(&self.clone().with_methods(allowed.iter().cloned()))
+        } else {
+            res.with_cors(self)
+        }
+    }
+}
+
+/// Using `Cors` as [`Middleware`] answers preflight requests with a `204 No Content` response
+/// carrying the configured headers, and applies the same headers to every other response.
+#[async_trait::async_trait(?Send)]
+impl<D> Middleware<D> for Cors {
+    async fn before(
+        &self,
+        req: &mut Request,
+        _ctx: &mut RouteContext<D>,
+    ) -> Result<Option<Response>> {
+        if req.method() == Method::Options && req.headers().has("Access-Control-Request-Method")? {
+            return self.preflight_response(&[]).map(Some);
+        }
+        Ok(None)
+    }
+
+    async fn after(&self, res: Response) -> Result<Response> {
+        res.with_cors(self)
+    }
 }
 
 fn concat_vec_to_string<S: AsRef<str>>(vec: &[S]) -> Result<String> {
@@ -0,0 +1,292 @@
+use std::collections::HashSet;
+
+use wasm_bindgen::JsValue;
+
+use crate::migrations::check_order;
+use crate::query::quote;
+use crate::{D1Database, D1PreparedStatement, Error, Migration, Result};
+
+/// The table wrangler records applied migrations in, unless configured otherwise.
+const DEFAULT_TABLE: &str = "d1_migrations";
+
+/// Applies schema migrations, usually embedded with [`embed_migrations!`](crate::embed_migrations),
+/// to a D1 database. Created with [`D1Database::migrations`].
+///
+/// Applied migrations are recorded in the same table as `wrangler d1 migrations apply`, under the
+/// name of their file, e.g. `0001_create_users.sql`, so either tool can be used on the same
+/// database. A migration recorded under a file name with the same version counts as applied.
+///
+/// ```ignore
+/// # use worker::*;
+/// const MIGRATIONS: &[Migration] = embed_migrations!("migrations");
+///
+/// # async fn run(env: Env) -> Result<()> {
+/// let db = env.d1("DB")?;
+/// let pending = db.migrations(MIGRATIONS).dry_run().run().await?;
+/// console_log!("{} pending migrations", pending.len());
+/// let applied = db.migrations(MIGRATIONS).run().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct D1Migrations<'a> {
+    db: &'a D1Database,
+    migrations: &'a [Migration],
+    table: String,
+    dry_run: bool,
+}
+
+impl D1Database {
+    /// Prepare to bring the schema of the database up to date with `migrations`, which must be
+    /// sorted by version.
+    pub fn migrations<'a>(&'a self, migrations: &'a [Migration]) -> D1Migrations<'a> {
+        D1Migrations {
+            db: self,
+            migrations,
+            table: DEFAULT_TABLE.to_string(),
+            dry_run: false,
+        }
+    }
+}
+
+impl D1Migrations<'_> {
+    /// Record applied migrations in `table` instead of `d1_migrations`, as with the
+    /// `migrations_table` option of wrangler.
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Only report the pending migrations, without changing the database.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Apply the pending migrations in order, returning them. In a dry run, only return them.
+    ///
+    /// The migrations and their records are applied in a single [`batch`](D1Database::batch),
+    /// so a failing migration leaves the schema unchanged.
+    pub async fn run(self) -> Result<Vec<Migration>> {
+        check_order(self.migrations)?;
+        let applied = self.applied().await?;
+        let pending: Vec<Migration> = self
+            .migrations
+            .iter()
+            .filter(|migration| !applied.contains(&migration.version))
+            .copied()
+            .collect();
+        if self.dry_run || pending.is_empty() {
+            return Ok(pending);
+        }
+
+        let mut statements = Vec::new();
+        for migration in &pending {
+            statements.extend(split_statements(migration.sql).map(|sql| self.db.prepare(sql)));
+            statements.push(self.record(migration)?);
+        }
+        self.db.batch(statements).await.map_err(|e| {
+            let versions: Vec<_> = pending.iter().map(|m| m.version.to_string()).collect();
+            Error::RustError(format!(
+                "applying migrations {} failed: {e}",
+                versions.join(", ")
+            ))
+        })?;
+        Ok(pending)
+    }
+
+    /// The versions of the migrations recorded in the table, creating it unless in a dry run.
+    async fn applied(&self) -> Result<HashSet<u32>> {
+        if self.dry_run {
+            let exists = self
+                .db
+                .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(&[JsValue::from(self.table.as_str())])?
+                .first::<serde_json::Value>(None)
+                .await?
+                .is_some();
+            if !exists {
+                return Ok(HashSet::new());
+            }
+        } else {
+            // The same definition as wrangler's.
+            self.db
+                .prepare(format!(
+                    "CREATE TABLE IF NOT EXISTS {} (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        name TEXT UNIQUE,
+                        applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+                    )",
+                    quote(&self.table)
+                ))
+                .run()
+                .await?;
+        }
+        let names = self
+            .db
+            .prepare(format!("SELECT name FROM {}", quote(&self.table)))
+            .raw::<Option<String>>()
+            .await?;
+        Ok(names
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|name| parse_version(&name))
+            .collect())
+    }
+
+    fn record(&self, migration: &Migration) -> Result<D1PreparedStatement> {
+        self.db
+            .prepare(format!(
+                "INSERT INTO {} (name) VALUES (?)",
+                quote(&self.table)
+            ))
+            .bind(&[JsValue::from(file_name(migration))])
+    }
+}
+
+/// The name of the file of `migration`, or the one wrangler would give it if it was not embedded
+/// from a file.
+fn file_name(migration: &Migration) -> String {
+    match (migration.file_name, migration.name) {
+        (Some(file_name), _) => file_name.to_string(),
+        (None, "") => format!("{:04}.sql", migration.version),
+        (None, name) => format!("{:04}_{name}.sql", migration.version),
+    }
+}
+
+/// The version of a migration recorded as `file_name`.
+fn parse_version(file_name: &str) -> Option<u32> {
+    let stem = file_name.strip_suffix(".sql").unwrap_or(file_name);
+    let version = stem.split_once('_').map_or(stem, |(version, _)| version);
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    version.parse().ok()
+}
+
+/// Split `sql` into its statements, since a prepared statement holds only one. Semicolons in
+/// strings, quoted identifiers and comments are ignored, as are those between the `BEGIN` and
+/// `END` of a `CREATE TRIGGER` and between `CASE` and `END`.
+fn split_statements(sql: &str) -> impl Iterator<Item = &str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    // Whether the current statement has anything but whitespace and comments.
+    let mut content = false;
+    // The first words of the current statement, to recognize a `CREATE TRIGGER`.
+    let mut words: Vec<String> = Vec::new();
+    // How many `BEGIN` or `CASE` blocks the current position is in.
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            delimiter @ (b'\'' | b'"' | b'`') => {
+                content = true;
+                i += 1;
+                while i < bytes.len() && bytes[i] != delimiter {
+                    i += 1;
+                }
+            }
+            b'[' => {
+                content = true;
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b';' => {
+                if depth == 0 {
+                    if content {
+                        statements.push(sql[start..i].trim());
+                    }
+                    start = i + 1;
+                    content = false;
+                    words.clear();
+                }
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                content = true;
+                let word_start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let word = sql[word_start..i].to_ascii_uppercase();
+                let trigger = words.first().is_some_and(|word| word == "CREATE")
+                    && words.iter().any(|word| word == "TRIGGER");
+                match word.as_str() {
+                    "BEGIN" if trigger => depth += 1,
+                    "CASE" => depth += 1,
+                    "END" => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                if words.len() < 3 {
+                    words.push(word);
+                }
+                continue;
+            }
+            b if !b.is_ascii_whitespace() => content = true,
+            _ => {}
+        }
+        i += 1;
+    }
+    if content {
+        statements.push(sql[start..].trim());
+    }
+    statements.into_iter()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_statements() {
+        let sql = "-- users; and more\n\
+                   CREATE TABLE users (id INTEGER, name TEXT DEFAULT 'a;b');\n\
+                   /* ; */ INSERT INTO \"we;ird\" VALUES (1);\n\
+                   CREATE TEMP TRIGGER t AFTER INSERT ON users BEGIN\n\
+                   DELETE FROM users; UPDATE users SET name = CASE WHEN id > 1 THEN 'x' END;\n\
+                   SELECT CASE name WHEN 'a' THEN 1 ELSE 2 END FROM users;\n\
+                   END;\n\
+                   BEGIN TRANSACTION; END;\n\
+                   ;  -- trailing";
+        let statements: Vec<_> = split_statements(sql).collect();
+        assert_eq!(statements.len(), 5);
+        assert_eq!(
+            statements[0],
+            "-- users; and more\nCREATE TABLE users (id INTEGER, name TEXT DEFAULT 'a;b')"
+        );
+        assert_eq!(statements[1], "/* ; */ INSERT INTO \"we;ird\" VALUES (1)");
+        assert!(statements[2].starts_with("CREATE TEMP TRIGGER"));
+        assert!(statements[2].ends_with("END FROM users;\nEND"));
+        assert_eq!(statements[3..], ["BEGIN TRANSACTION", "END"]);
+        assert_eq!(
+            split_statements("SELECT 1").collect::<Vec<_>>(),
+            ["SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn names_migrations_like_wrangler() {
+        let migration = Migration::new(3, "add_users", "");
+        assert_eq!(file_name(&migration), "0003_add_users.sql");
+        assert_eq!(file_name(&Migration::new(12, "", "")), "0012.sql");
+        let migration = migration.with_file_name("003_add_users.sql");
+        assert_eq!(file_name(&migration), "003_add_users.sql");
+        assert_eq!(parse_version("0003_add_users.sql"), Some(3));
+        assert_eq!(parse_version("3.sql"), Some(3));
+        assert_eq!(parse_version("init.sql"), None);
+    }
+}
@@ -4,8 +4,10 @@ use std::future::{Future, IntoFuture};
 use std::iter::{once, Once};
 use std::ops::Deref;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::result::Result as StdResult;
 
+use futures_util::TryStreamExt;
 use js_sys::futures::JsFuture;
 use js_sys::Array;
 use js_sys::ArrayBuffer;
@@ -22,10 +24,14 @@ use worker_sys::types::D1Result as D1ResultSys;
 use crate::env::EnvBinding;
 use crate::Error;
 use crate::Result;
+use crate::{FromRow, SqlRowStream, SqlStorageValue, ToParams};
 
 pub use serde_wasm_bindgen;
 
 pub mod macros;
+mod migrations;
+
+pub use migrations::D1Migrations;
 
 // A D1 Database.
 #[derive(Debug)]
@@ -433,6 +439,55 @@ impl D1PreparedStatement {
         Ok(vec)
     }
 
+    /// Bind the parameters of `params`, usually derived with `#[derive(ToParams)]`, to the
+    /// statement.
+    pub fn bind_params(self, params: &impl ToParams) -> Result<Self> {
+        let values: Vec<JsValue> = params.to_params().into_iter().map(Into::into).collect();
+        self.bind(&values)
+    }
+
+    /// Executes a query against the database and converts the rows with [`FromRow`], usually
+    /// derived with `#[derive(FromRow)]`. Unlike [`all`](D1PreparedStatement::all), this keeps
+    /// integers, blobs and `NULL`s distinct from other values.
+    pub async fn rows<T: FromRow>(&self) -> Result<Vec<T>> {
+        self.stream()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .map(T::from_row)
+            .collect()
+    }
+
+    /// Executes a query against the database, returning its rows as an async
+    /// [`Stream`](futures_util::Stream), which can also be encoded into a response with
+    /// [`SqlRowStream::export`](crate::SqlRowStream::export).
+    pub async fn stream(&self) -> Result<SqlRowStream> {
+        let options = js_sys::Object::new();
+        js_sys::Reflect::set(&options, &"columnNames".into(), &JsValue::TRUE)?;
+        let result = JsFuture::from(self.0.raw_with_options(&options)?).await;
+        let result = cast_to_d1_error(result)?.dyn_into::<Array>()?;
+
+        // With `columnNames`, the first row holds the names of the columns.
+        let mut rows = result.to_vec().into_iter();
+        let columns: Rc<[String]> = match rows.next() {
+            Some(names) => Array::from(&names)
+                .iter()
+                .map(|name| name.as_string().unwrap_or_default())
+                .collect(),
+            None => Rc::from([]),
+        };
+        Ok(SqlRowStream::new(
+            columns,
+            rows.map(|row| {
+                Array::from(&row)
+                    .iter()
+                    .map(SqlStorageValue::try_from)
+                    .collect()
+            }),
+        ))
+    }
+
     /// Executes a query against the database and returns a `Vec` of JsValues.
     pub async fn raw_js_value(&self) -> Result<Vec<JsValue>> {
         let result = JsFuture::from(self.0.raw()?).await;
@@ -27,7 +27,7 @@ use futures_util::Future;
 use js_sys::futures::{future_to_promise, JsFuture};
 use js_sys::{Map, Number, Object};
 use serde::{de::DeserializeOwned, Serialize};
-use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen::{closure::ScopedClosure, prelude::*, JsCast};
 use worker_sys::{
     DurableObject as EdgeDurableObject, DurableObjectId,
     DurableObjectNamespace as EdgeObjectNamespace, DurableObjectState, DurableObjectStorage,
@@ -61,6 +61,39 @@ impl Stub {
     pub fn into_rpc<T: JsCast>(self) -> T {
         self.inner.unchecked_into()
     }
+
+    /// Call the RPC method named `method` on the Durable Object with arguments that were
+    /// serialized with [`rpc::to_js`], and deserialize its result.
+    ///
+    /// Methods exported with `#[durable_object(rpc)]` are more conveniently called through the
+    /// typed stub generated for them.
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, args: &[JsValue]) -> Result<T> {
+        let function: js_sys::Function =
+            js_sys::Reflect::get(&self.inner, &JsValue::from(method))?.unchecked_into();
+        let args: js_sys::Array = args.iter().collect();
+        let value = function.apply(&self.inner, &args)?;
+        let value = JsFuture::from(js_sys::Promise::resolve(&value)).await?;
+        rpc::from_js(value)
+    }
+}
+
+/// Conversion of RPC arguments and results between Rust and JavaScript values, as JSON-compatible
+/// objects so that JavaScript callers can use them directly.
+pub mod rpc {
+    use serde::{de::DeserializeOwned, Serialize};
+    use wasm_bindgen::JsValue;
+
+    use crate::Result;
+
+    /// Serialize an RPC argument or result.
+    pub fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue> {
+        Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+    }
+
+    /// Deserialize an RPC argument or result.
+    pub fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T> {
+        Ok(serde_wasm_bindgen::from_value(value)?)
+    }
 }
 
 /// Use an ObjectNamespace to get access to Stubs for communication with a Durable Object instance.
@@ -572,9 +605,116 @@ impl Storage {
             .map(|_| ())
     }
 
+    /// Run `closure` synchronously in a transaction, so that the storage operations it performs,
+    /// through [`sql`](Storage::sql) or [`kv`](Storage::kv), are applied atomically. They are
+    /// rolled back if the closure returns an error or panics.
+    ///
+    /// The closure can't await, so the asynchronous methods of `Storage` can't be used in it.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// # fn run(state: State) -> Result<()> {
+    /// let storage = state.storage();
+    /// let sql = storage.sql();
+    /// storage.transaction_sync(|| {
+    ///     sql.exec("UPDATE accounts SET balance = balance - 10 WHERE id = ?", vec![1.into()])?;
+    ///     sql.exec("UPDATE accounts SET balance = balance + 10 WHERE id = ?", vec![2.into()])?;
+    ///     storage.kv().put("last_transfer", Date::now().as_millis())
+    /// })
+    /// # }
+    /// ```
+    pub fn transaction_sync<T>(&self, closure: impl FnOnce() -> Result<T>) -> Result<T> {
+        transaction_sync(&self.inner, closure)
+    }
+
+    /// Wait until all the writes made so far have been persisted to disk.
+    ///
+    /// Writes are normally confirmed before any response or outbound message is sent anyway;
+    /// this is useful to make sure a write is durable before continuing otherwise.
+    pub async fn sync(&self) -> Result<()> {
+        JsFuture::from(self.inner.sync()?).await?;
+        Ok(())
+    }
+
+    /// The synchronous key-value API of SQLite-backed Durable Objects, which can be used in
+    /// [`transaction_sync`](Storage::transaction_sync).
+    pub fn kv(&self) -> SyncKv {
+        SyncKv {
+            inner: self.inner.kv(),
+        }
+    }
+
     // Add new method to access SQLite APIs
     pub fn sql(&self) -> crate::sql::SqlStorage {
-        crate::sql::SqlStorage::new(self.inner.sql())
+        crate::sql::SqlStorage::new(self.inner.sql(), self.inner.clone())
+    }
+}
+
+/// Run `closure` in a `transactionSync` of `storage`, rolling back if it fails.
+pub(crate) fn transaction_sync<T>(
+    storage: &DurableObjectStorage,
+    closure: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let mut closure = Some(closure);
+    let mut output = None;
+    let mut callback = || -> std::result::Result<JsValue, JsValue> {
+        let closure = closure
+            .take()
+            .ok_or_else(|| JsValue::from("transaction callback called twice"))?;
+        let result = closure();
+        let rollback = result.is_err();
+        output = Some(result);
+        match rollback {
+            // Throw so that the runtime rolls the transaction back. The error itself is
+            // returned from `output`.
+            true => Err(JsValue::from("transaction rolled back")),
+            false => Ok(JsValue::UNDEFINED),
+        }
+    };
+    // A panic in the closure is caught and thrown as an exception, which rolls back too.
+    let js_callback = ScopedClosure::borrow_mut_assert_unwind_safe(&mut callback);
+    let result = storage.transaction_sync(&js_callback);
+    drop(js_callback);
+
+    match (output, result) {
+        (Some(Err(e)), _) => Err(e),
+        (_, Err(e)) => Err(e.into()),
+        (Some(Ok(value)), Ok(_)) => Ok(value),
+        (None, Ok(_)) => Err(Error::RustError(
+            "transaction callback was not called".into(),
+        )),
+    }
+}
+
+/// The synchronous key-value API of SQLite-backed Durable Objects. Obtained with
+/// [`Storage::kv`].
+#[derive(Debug, Clone)]
+pub struct SyncKv {
+    inner: worker_sys::SyncKvStorage,
+}
+
+impl SyncKv {
+    /// Retrieves the value associated with the given key.
+    ///
+    /// Returns `Ok(None)` if the key does not exist.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let val = self.inner.get(key)?;
+        if val.is_undefined() {
+            return Ok(None);
+        }
+        Ok(Some(serde_wasm_bindgen::from_value(val)?))
+    }
+
+    /// Stores the value and associates it with the given key.
+    pub fn put<T: Serialize>(&self, key: &str, value: T) -> Result<()> {
+        self.inner
+            .put(key, serde_wasm_bindgen::to_value(&value)?)
+            .map_err(Error::from)
+    }
+
+    /// Deletes the key and associated value. Returns true if the key existed.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        self.inner.delete(key).map_err(Error::from)
     }
 }
 
@@ -765,7 +905,7 @@ impl ScheduledTime {
         }
     }
 
-    fn schedule(self) -> js_sys::Date {
+    pub(crate) fn schedule(self) -> js_sys::Date {
         match self.init {
             ScheduledTimeInit::Date(date) => date,
             ScheduledTimeInit::Offset(offset_ms) => {
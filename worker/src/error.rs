@@ -9,6 +9,11 @@ use wasm_bindgen::{JsCast, JsValue};
 pub enum Error {
     BadEncoding,
     BodyUsed,
+    /// A request body was larger than the limit it was read with. The router answers it with
+    /// `413 Payload Too Large`.
+    PayloadTooLarge {
+        limit: usize,
+    },
     Json((String, u16)),
     /// Error used for strings thrown from JS
     JsError(String),
@@ -232,6 +237,12 @@ impl std::fmt::Display for Error {
             Error::RustError(s) => f.write_str(s),
             Error::BindingError(name) => write!(f, "no binding found for `{name}`"),
             Error::Json((msg, status)) => write!(f, "{msg} (status: {status})"),
+            Error::PayloadTooLarge { limit } => {
+                write!(
+                    f,
+                    "payload too large: body exceeds the limit of {limit} bytes"
+                )
+            }
             // Wrapped Rust errors: source() exposes the inner; here we just
             // categorize. Avoids duplication when consumers walk the chain.
             Error::Io(_) => f.write_str("I/O error"),
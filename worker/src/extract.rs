@@ -0,0 +1,212 @@
+use std::future::Future;
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    env::Env,
+    headers::Headers,
+    http::Method,
+    request::Request,
+    response::Response,
+    router::{RouteContext, RouteParams},
+    Error, Result,
+};
+
+/// A type that can be extracted from a request routed by a [`Router`](crate::Router), for use as
+/// an argument of handlers registered with [`Router::route`](crate::Router::route).
+///
+/// Where [`FromRequest`](crate::FromRequest) converts the raw request handed to the fetch event,
+/// `FromRouteRequest` extracts a single piece of it, possibly consuming the body, and may also use
+/// the route's parameters, shared data and environment. Extractors run in argument order; an
+/// extraction failure turns into a `400 Bad Request` response without calling the handler.
+#[async_trait::async_trait(?Send)]
+pub trait FromRouteRequest<D>: Sized {
+    async fn from_route_request(req: &mut Request, ctx: &RouteContext<D>) -> Result<Self>;
+}
+
+/// A handler function whose arguments are all [`FromRouteRequest`] extractors.
+///
+/// This is implemented for `async` functions and closures taking up to eight extractors and
+/// returning `Result<Response>`.
+pub trait RouteHandler<Args> {
+    type Future: Future<Output = Result<Response>>;
+
+    fn call(&self, args: Args) -> Self::Future;
+}
+
+macro_rules! impl_extract_tuple {
+    ($($ty:ident),*) => {
+        impl<F, Fut, $($ty,)*> RouteHandler<($($ty,)*)> for F
+        where
+            F: Fn($($ty),*) -> Fut,
+            Fut: Future<Output = Result<Response>>,
+        {
+            type Future = Fut;
+
+            #[allow(non_snake_case)]
+            fn call(&self, ($($ty,)*): ($($ty,)*)) -> Self::Future {
+                (self)($($ty),*)
+            }
+        }
+
+        #[async_trait::async_trait(?Send)]
+        impl<D, $($ty: FromRouteRequest<D>,)*> FromRouteRequest<D> for ($($ty,)*) {
+            #[allow(unused_variables)]
+            async fn from_route_request(req: &mut Request, ctx: &RouteContext<D>) -> Result<Self> {
+                Ok(($($ty::from_route_request(req, ctx).await?,)*))
+            }
+        }
+    };
+}
+
+impl_extract_tuple!();
+impl_extract_tuple!(T1);
+impl_extract_tuple!(T1, T2);
+impl_extract_tuple!(T1, T2, T3);
+impl_extract_tuple!(T1, T2, T3, T4);
+impl_extract_tuple!(T1, T2, T3, T4, T5);
+impl_extract_tuple!(T1, T2, T3, T4, T5, T6);
+impl_extract_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_extract_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// Extracts the request body, deserialized from JSON.
+#[derive(Debug, Clone)]
+pub struct Json<T>(pub T);
+
+#[async_trait::async_trait(?Send)]
+impl<D, T: DeserializeOwned> FromRouteRequest<D> for Json<T> {
+    async fn from_route_request(req: &mut Request, _ctx: &RouteContext<D>) -> Result<Self> {
+        req.json().await.map(Json)
+    }
+}
+
+/// Extracts the request body, deserialized from an `application/x-www-form-urlencoded` payload.
+/// Use [`Request::form_data`] for `multipart/form-data` bodies.
+#[derive(Debug, Clone)]
+pub struct Form<T>(pub T);
+
+#[async_trait::async_trait(?Send)]
+impl<D, T: DeserializeOwned> FromRouteRequest<D> for Form<T> {
+    async fn from_route_request(req: &mut Request, _ctx: &RouteContext<D>) -> Result<Self> {
+        let body = req.text().await?;
+        serde_urlencoded::from_str(&body)
+            .map(Form)
+            .map_err(Error::from)
+    }
+}
+
+/// Extracts the URL query, deserialized with [`Request::query`].
+#[derive(Debug, Clone)]
+pub struct Query<T>(pub T);
+
+#[async_trait::async_trait(?Send)]
+impl<D, T: DeserializeOwned> FromRouteRequest<D> for Query<T> {
+    async fn from_route_request(req: &mut Request, _ctx: &RouteContext<D>) -> Result<Self> {
+        req.query().map(Query)
+    }
+}
+
+/// Extracts the parameters captured by the route's pattern, deserialized into a struct or map
+/// keyed by parameter name. Values are parsed from their string form, so a `/user/:id` route can
+/// be extracted into a struct with an `id: u64` field.
+#[derive(Debug, Clone)]
+pub struct Path<T>(pub T);
+
+#[async_trait::async_trait(?Send)]
+impl<D, T: DeserializeOwned> FromRouteRequest<D> for Path<T> {
+    async fn from_route_request(_req: &mut Request, ctx: &RouteContext<D>) -> Result<Self> {
+        deserialize_params(ctx.params()).map(Path)
+    }
+}
+
+fn deserialize_params<T: DeserializeOwned>(params: &RouteParams) -> Result<T> {
+    let encoded = serde_urlencoded::to_string(params.inner()).map_err(|e| e.to_string())?;
+    serde_urlencoded::from_str(&encoded).map_err(Error::from)
+}
+
+/// Extracts a clone of the route's shared data.
+#[derive(Debug, Clone)]
+pub struct Data<D>(pub D);
+
+#[async_trait::async_trait(?Send)]
+impl<D: Clone> FromRouteRequest<D> for Data<D> {
+    async fn from_route_request(_req: &mut Request, ctx: &RouteContext<D>) -> Result<Self> {
+        Ok(Data(ctx.data.clone()))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<D> FromRouteRequest<D> for Headers {
+    async fn from_route_request(req: &mut Request, _ctx: &RouteContext<D>) -> Result<Self> {
+        Ok(req.headers().clone())
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<D> FromRouteRequest<D> for Method {
+    async fn from_route_request(req: &mut Request, _ctx: &RouteContext<D>) -> Result<Self> {
+        Ok(req.method())
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<D> FromRouteRequest<D> for Env {
+    async fn from_route_request(_req: &mut Request, ctx: &RouteContext<D>) -> Result<Self> {
+        Ok(ctx.env.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct UserPath {
+        account: String,
+        id: u64,
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> RouteParams {
+        RouteParams::from(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn path_params_deserialize_into_struct() {
+        let path: UserPath = deserialize_params(&params(&[("account", "acme"), ("id", "42")]))
+            .expect("deserialize params");
+        assert_eq!(
+            path,
+            UserPath {
+                account: "acme".into(),
+                id: 42
+            }
+        );
+    }
+
+    #[test]
+    fn path_params_reject_invalid_values() {
+        let res: Result<UserPath> =
+            deserialize_params(&params(&[("account", "acme"), ("id", "not-a-number")]));
+        assert!(res.is_err());
+
+        let res: Result<UserPath> = deserialize_params(&params(&[("id", "1")]));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn path_params_deserialize_into_map() {
+        let map: HashMap<String, String> =
+            deserialize_params(&params(&[("a", "1 2"), ("b", "x/y")])).expect("deserialize params");
+        assert_eq!(map["a"], "1 2");
+        assert_eq!(map["b"], "x/y");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{env::EnvBinding, RequestInit, Result};
+use crate::{env::EnvBinding, intercept, RequestInit, Result};
 use js_sys::futures::JsFuture;
 use std::convert::TryInto;
 use wasm_bindgen::{JsCast, JsValue};
@@ -26,12 +26,20 @@ impl Fetcher {
         init: Option<RequestInit>,
     ) -> Result<FetchResponseType> {
         let path = url.into();
-        let promise = match init {
-            Some(ref init) => self.0.fetch_with_str_and_init(&path, &init.into()),
-            None => self.0.fetch_with_str(&path),
-        }?;
+        let resp_sys: web_sys::Response = if intercept::active() {
+            let req = match init {
+                Some(ref init) => Request::new_with_init(&path, init)?,
+                None => Request::new(&path, crate::Method::Get)?,
+            };
+            self.fetch_response(req).await?.into()
+        } else {
+            let promise = match init {
+                Some(ref init) => self.0.fetch_with_str_and_init(&path, &init.into()),
+                None => self.0.fetch_with_str(&path),
+            }?;
+            JsFuture::from(promise).await?.dyn_into()?
+        };
 
-        let resp_sys: web_sys::Response = JsFuture::from(promise).await?.dyn_into()?;
         #[cfg(not(feature = "http"))]
         let result = Ok(Response::from(resp_sys));
         #[cfg(feature = "http")]
@@ -50,10 +58,7 @@ impl Fetcher {
         T: TryInto<Request, Error = E>,
         crate::Error: From<E>,
     {
-        let req = request.try_into()?;
-        let promise = self.0.fetch(req.inner())?;
-        let resp_sys: web_sys::Response = JsFuture::from(promise).await?.dyn_into()?;
-        let response = Response::from(resp_sys);
+        let response = self.fetch_response(request.try_into()?).await?;
         #[cfg(feature = "http")]
         let result = response.try_into();
         #[cfg(not(feature = "http"))]
@@ -61,6 +66,22 @@ impl Fetcher {
         result
     }
 
+    /// Like [`fetch_request`](Fetcher::fetch_request), always returning a [`Response`].
+    pub(crate) async fn fetch_response(&self, request: Request) -> Result<Response> {
+        let send = |req: Request| -> futures_util::future::LocalBoxFuture<'_, Result<Response>> {
+            Box::pin(async move {
+                let promise = self.0.fetch(req.inner())?;
+                let resp_sys: web_sys::Response = JsFuture::from(promise).await?.dyn_into()?;
+                Ok(Response::from(resp_sys))
+            })
+        };
+        if intercept::active() {
+            intercept::run(request, &send).await
+        } else {
+            send(request).await
+        }
+    }
+
     /// Convert Fetcher into user-defined RPC interface.
     /// ```
     /// #[wasm_bindgen]
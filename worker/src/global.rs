@@ -3,7 +3,7 @@ use std::ops::Deref;
 use js_sys::futures::JsFuture;
 use wasm_bindgen::JsCast;
 
-use crate::{request::Request, response::Response, AbortSignal, Result};
+use crate::{intercept, request::Request, response::Response, AbortSignal, Method, Result};
 
 /// Construct a Fetch call from a URL string or a Request object. Call its `send` method to execute
 /// the request.
@@ -16,19 +16,42 @@ pub enum Fetch {
 impl Fetch {
     /// Execute a Fetch call and receive a Response.
     pub async fn send(&self) -> Result<Response> {
-        match self {
-            Fetch::Url(url) => fetch_with_str(url.as_ref(), None).await,
-            Fetch::Request(req) => fetch_with_request(req, None).await,
-        }
+        self.send_inner(None).await
     }
 
     /// Execute a Fetch call and receive a Response.
     pub async fn send_with_signal(&self, signal: &AbortSignal) -> Result<Response> {
-        match self {
-            Fetch::Url(url) => fetch_with_str(url.as_ref(), Some(signal)).await,
-            Fetch::Request(req) => fetch_with_request(req, Some(signal)).await,
+        self.send_inner(Some(signal)).await
+    }
+
+    async fn send_inner(&self, signal: Option<&AbortSignal>) -> Result<Response> {
+        if !intercept::active() {
+            return match self {
+                Fetch::Url(url) => fetch_with_str(url.as_ref(), signal).await,
+                Fetch::Request(req) => fetch_with_request(req, signal).await,
+            };
         }
+
+        let req = match self {
+            Fetch::Url(url) => Request::new(url.as_str(), Method::Get)?,
+            Fetch::Request(req) => req.clone()?,
+        };
+        intercept::run(req, &|req| {
+            Box::pin(async move { fetch_with_request(&req, signal).await })
+        })
+        .await
+    }
+}
+
+/// Fetch `request` with the global `fetch`, through any registered interceptors.
+pub(crate) async fn fetch(request: Request) -> Result<Response> {
+    if !intercept::active() {
+        return fetch_with_request(&request, None).await;
     }
+    intercept::run(request, &|req| {
+        Box::pin(async move { fetch_with_request(&req, None).await })
+    })
+    .await
 }
 
 async fn fetch_with_str(url: &str, signal: Option<&AbortSignal>) -> Result<Response> {
@@ -11,6 +11,9 @@ use js_sys::Array;
 use wasm_bindgen::JsValue;
 use worker_sys::ext::HeadersExt;
 
+mod typed;
+pub use typed::*;
+
 /// A [Headers](https://developer.mozilla.org/en-US/docs/Web/API/Headers) representation used in
 /// Request and Response objects.
 pub struct Headers(pub web_sys::Headers);
@@ -108,6 +111,46 @@ impl Headers {
             })
             .collect()
     }
+
+    /// Parses the header `H`. Returns `None` if the header is absent, and an error if it
+    /// is malformed.
+    pub fn header<H: TypedHeader>(&self) -> Result<Option<H>> {
+        self.get(H::name().as_str())?
+            .map(|value| H::parse(&value))
+            .transpose()
+    }
+
+    /// Sets the header `H`, replacing any existing value.
+    pub fn set_header<H: TypedHeader>(&self, header: &H) -> Result<()> {
+        self.set(H::name().as_str(), &header.to_value())
+    }
+
+    /// Decodes a header implementing [`::headers::Header`]. Returns `None` if the header is absent.
+    #[cfg(feature = "http")]
+    pub fn typed_get<H: ::headers::Header>(&self) -> Result<Option<H>> {
+        let Some(value) = self.get(H::name().as_str())? else {
+            return Ok(None);
+        };
+        let value = HeaderValue::from_str(&value)?;
+        H::decode(&mut std::iter::once(&value))
+            .map(Some)
+            .map_err(|e| Error::RustError(format!("invalid {} header: {e}", H::name())))
+    }
+
+    /// Encodes a header implementing [`::headers::Header`], replacing any existing value.
+    #[cfg(feature = "http")]
+    pub fn typed_insert<H: ::headers::Header>(&self, header: H) -> Result<()> {
+        let mut values = Vec::new();
+        header.encode(&mut values);
+        self.delete(H::name().as_str())?;
+        for value in values {
+            let value = value
+                .to_str()
+                .map_err(|e| Error::RustError(e.to_string()))?;
+            self.append(H::name().as_str(), value)?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for Headers {
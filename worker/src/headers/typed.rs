@@ -0,0 +1,1143 @@
+use std::time::Duration;
+
+use http::header::{self, HeaderName};
+
+use crate::{base64, Error, Result};
+
+/// A header that can be parsed from and rendered to its value, for use with
+/// [`Headers::header`](crate::Headers::header) and
+/// [`Headers::set_header`](crate::Headers::set_header).
+///
+/// With the `http` feature, the types implementing this trait also implement
+/// [`headers::Header`](::headers::Header), so they can be used with `http::HeaderMap` too.
+pub trait TypedHeader: Sized {
+    /// The name of the header.
+    fn name() -> &'static HeaderName;
+
+    /// Parse the header from its value. Repeated headers are passed joined with `, `.
+    fn parse(value: &str) -> Result<Self>;
+
+    /// Render the value of the header.
+    fn to_value(&self) -> String;
+}
+
+fn invalid<H: TypedHeader>(value: &str) -> Error {
+    Error::RustError(format!("invalid {} header: {value:?}", H::name()))
+}
+
+/// Split `value` on `sep`, ignoring separators inside quoted strings. Parts are trimmed and empty
+/// parts are skipped.
+fn split_quoted(value: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == sep && !quoted => {
+                parts.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Parse a parameter value, which is either a token or a quoted string.
+fn param_value(value: &str) -> Option<String> {
+    let Some(quoted) = value.strip_prefix('"') else {
+        return is_token(value).then(|| value.to_string());
+    };
+    let quoted = quoted.strip_suffix('"')?;
+    let mut out = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?),
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// Render a parameter value, quoting it unless it is a token.
+fn render_param_value(value: &str) -> String {
+    if is_token(value) {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Parse `name=value` parameters following a media type.
+fn parse_params(parts: &[&str]) -> Option<Vec<(String, String)>> {
+    parts
+        .iter()
+        .map(|param| {
+            let (name, value) = param.split_once('=')?;
+            let name = name.trim();
+            if !is_token(name) {
+                return None;
+            }
+            Some((name.to_ascii_lowercase(), param_value(value.trim())?))
+        })
+        .collect()
+}
+
+fn render_params(out: &mut String, params: &[(String, String)]) {
+    for (name, value) in params {
+        out.push_str("; ");
+        out.push_str(name);
+        out.push('=');
+        out.push_str(&render_param_value(value));
+    }
+}
+
+/// Parse a media type or range such as `text/html`, `text/*` or `*/*` into its lowercase form.
+fn parse_essence(value: &str) -> Option<String> {
+    let (ty, subtype) = value.split_once('/')?;
+    (is_token(ty) && is_token(subtype)).then(|| value.to_ascii_lowercase())
+}
+
+/// The `Content-Type` header: a media type with optional parameters.
+///
+/// ```no_run
+/// # use worker::{headers::ContentType, *};
+/// # fn main() -> Result<()> {
+/// # let headers = Headers::new();
+/// let content_type = headers.header::<ContentType>()?;
+/// if content_type.is_some_and(|ct| ct.essence() == "application/json") {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentType {
+    essence: String,
+    params: Vec<(String, String)>,
+}
+
+impl ContentType {
+    /// A media type without parameters, such as `application/json`.
+    ///
+    /// # Panics
+    ///
+    /// If `essence` isn't a valid `type/subtype` pair.
+    pub fn new(essence: &str) -> Self {
+        let essence =
+            parse_essence(essence).unwrap_or_else(|| panic!("invalid media type `{essence}`"));
+        Self {
+            essence,
+            params: Vec::new(),
+        }
+    }
+
+    /// `application/json`
+    pub fn json() -> Self {
+        Self::new("application/json")
+    }
+
+    /// `text/html; charset=utf-8`
+    pub fn html() -> Self {
+        Self::new("text/html").with_param("charset", "utf-8")
+    }
+
+    /// `text/plain; charset=utf-8`
+    pub fn text() -> Self {
+        Self::new("text/plain").with_param("charset", "utf-8")
+    }
+
+    /// `application/x-www-form-urlencoded`
+    pub fn form_url_encoded() -> Self {
+        Self::new("application/x-www-form-urlencoded")
+    }
+
+    /// `application/octet-stream`
+    pub fn octet_stream() -> Self {
+        Self::new("application/octet-stream")
+    }
+
+    /// Add a parameter, replacing any parameter with the same name.
+    pub fn with_param(mut self, name: &str, value: impl Into<String>) -> Self {
+        let name = name.to_ascii_lowercase();
+        self.params.retain(|(n, _)| *n != name);
+        self.params.push((name, value.into()));
+        self
+    }
+
+    /// The media type without its parameters, in lowercase, such as `text/html`.
+    pub fn essence(&self) -> &str {
+        &self.essence
+    }
+
+    /// The value of the parameter named `name`, which is case-insensitive.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of the `charset` parameter.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+}
+
+impl TypedHeader for ContentType {
+    fn name() -> &'static HeaderName {
+        &header::CONTENT_TYPE
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let parts = split_quoted(value, ';');
+        let (essence, params) = parts.split_first().ok_or_else(|| invalid::<Self>(value))?;
+        Ok(Self {
+            essence: parse_essence(essence).ok_or_else(|| invalid::<Self>(value))?,
+            params: parse_params(params).ok_or_else(|| invalid::<Self>(value))?,
+        })
+    }
+
+    fn to_value(&self) -> String {
+        let mut out = self.essence.clone();
+        render_params(&mut out, &self.params);
+        out
+    }
+}
+
+/// The `Cache-Control` header. Durations are rendered in whole seconds.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use worker::{headers::CacheControl, *};
+/// # fn main() -> Result<()> {
+/// let headers = Headers::new();
+/// headers.set_header(&CacheControl {
+///     public: true,
+///     max_age: Some(Duration::from_secs(3600)),
+///     ..Default::default()
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    pub no_cache: bool,
+    pub no_store: bool,
+    pub no_transform: bool,
+    pub must_revalidate: bool,
+    pub proxy_revalidate: bool,
+    pub must_understand: bool,
+    pub public: bool,
+    pub private: bool,
+    pub immutable: bool,
+    pub only_if_cached: bool,
+    pub max_age: Option<Duration>,
+    pub s_max_age: Option<Duration>,
+    pub max_stale: Option<Duration>,
+    pub min_fresh: Option<Duration>,
+    pub stale_while_revalidate: Option<Duration>,
+    pub stale_if_error: Option<Duration>,
+    /// Directives not covered by the other fields, with their optional value.
+    pub extensions: Vec<(String, Option<String>)>,
+}
+
+impl TypedHeader for CacheControl {
+    fn name() -> &'static HeaderName {
+        &header::CACHE_CONTROL
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let mut cc = CacheControl::default();
+        for directive in split_quoted(value, ',') {
+            let (name, arg) = match directive.split_once('=') {
+                Some((name, arg)) => (name.trim(), Some(arg.trim())),
+                None => (directive, None),
+            };
+            if !is_token(name) {
+                return Err(invalid::<Self>(value));
+            }
+            let arg = arg
+                .map(|arg| param_value(arg).ok_or_else(|| invalid::<Self>(value)))
+                .transpose()?;
+            let seconds = || -> Result<Option<Duration>> {
+                let arg = arg.as_deref().ok_or_else(|| invalid::<Self>(value))?;
+                let secs = arg.parse().map_err(|_| invalid::<Self>(value))?;
+                Ok(Some(Duration::from_secs(secs)))
+            };
+            match name.to_ascii_lowercase().as_str() {
+                // `no-cache` and `private` may list header names, which are not kept.
+                "no-cache" => cc.no_cache = true,
+                "no-store" => cc.no_store = true,
+                "no-transform" => cc.no_transform = true,
+                "must-revalidate" => cc.must_revalidate = true,
+                "proxy-revalidate" => cc.proxy_revalidate = true,
+                "must-understand" => cc.must_understand = true,
+                "public" => cc.public = true,
+                "private" => cc.private = true,
+                "immutable" => cc.immutable = true,
+                "only-if-cached" => cc.only_if_cached = true,
+                "max-age" => cc.max_age = seconds()?,
+                "s-maxage" => cc.s_max_age = seconds()?,
+                "max-stale" => cc.max_stale = seconds()?,
+                "min-fresh" => cc.min_fresh = seconds()?,
+                "stale-while-revalidate" => cc.stale_while_revalidate = seconds()?,
+                "stale-if-error" => cc.stale_if_error = seconds()?,
+                name => cc.extensions.push((name.to_string(), arg)),
+            }
+        }
+        Ok(cc)
+    }
+
+    fn to_value(&self) -> String {
+        let flags = [
+            ("public", self.public),
+            ("private", self.private),
+            ("no-cache", self.no_cache),
+            ("no-store", self.no_store),
+            ("no-transform", self.no_transform),
+            ("must-revalidate", self.must_revalidate),
+            ("proxy-revalidate", self.proxy_revalidate),
+            ("must-understand", self.must_understand),
+            ("immutable", self.immutable),
+            ("only-if-cached", self.only_if_cached),
+        ];
+        let durations = [
+            ("max-age", self.max_age),
+            ("s-maxage", self.s_max_age),
+            ("max-stale", self.max_stale),
+            ("min-fresh", self.min_fresh),
+            ("stale-while-revalidate", self.stale_while_revalidate),
+            ("stale-if-error", self.stale_if_error),
+        ];
+
+        let mut directives = flags
+            .into_iter()
+            .filter(|(_, set)| *set)
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        directives.extend(
+            durations
+                .into_iter()
+                .filter_map(|(name, d)| d.map(|d| format!("{name}={}", d.as_secs()))),
+        );
+        directives.extend(self.extensions.iter().map(|(name, arg)| match arg {
+            Some(arg) => format!("{name}={}", render_param_value(arg)),
+            None => name.clone(),
+        }));
+        directives.join(", ")
+    }
+}
+
+/// An entity tag, as sent in the `ETag` header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ETag {
+    tag: String,
+    weak: bool,
+}
+
+impl ETag {
+    /// A strong entity tag, changing whenever the representation changes.
+    ///
+    /// # Panics
+    ///
+    /// If `tag` contains a double quote, a control character or a space.
+    pub fn strong(tag: impl Into<String>) -> Self {
+        Self::new(tag.into(), false)
+    }
+
+    /// A weak entity tag, changing only when the representation changes meaningfully.
+    ///
+    /// # Panics
+    ///
+    /// If `tag` contains a double quote, a control character or a space.
+    pub fn weak(tag: impl Into<String>) -> Self {
+        Self::new(tag.into(), true)
+    }
+
+    fn new(tag: String, weak: bool) -> Self {
+        assert!(is_etagc(&tag), "invalid entity tag `{tag}`");
+        Self { tag, weak }
+    }
+
+    /// The opaque tag, without quotes or weakness indicator.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Whether both tags are strong and identical, as required for `If-Match` and `Range`.
+    pub fn strong_eq(&self, other: &ETag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Whether both tags are identical, regardless of weakness, as used for `If-None-Match`.
+    pub fn weak_eq(&self, other: &ETag) -> bool {
+        self.tag == other.tag
+    }
+
+    fn parse_tag(value: &str) -> Option<Self> {
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, value),
+        };
+        let tag = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        is_etagc(tag).then(|| Self {
+            tag: tag.to_string(),
+            weak,
+        })
+    }
+
+    fn render(&self) -> String {
+        match self.weak {
+            true => format!("W/\"{}\"", self.tag),
+            false => format!("\"{}\"", self.tag),
+        }
+    }
+}
+
+fn is_etagc(tag: &str) -> bool {
+    tag.bytes()
+        .all(|b| b == 0x21 || (0x23..=0x7E).contains(&b) || b >= 0x80)
+}
+
+impl TypedHeader for ETag {
+    fn name() -> &'static HeaderName {
+        &header::ETAG
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        ETag::parse_tag(value.trim()).ok_or_else(|| invalid::<Self>(value))
+    }
+
+    fn to_value(&self) -> String {
+        self.render()
+    }
+}
+
+/// The `If-None-Match` header, listing the entity tags of the representations the client
+/// already has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfNoneMatch {
+    /// `*`, matching any current representation.
+    Any,
+    Tags(Vec<ETag>),
+}
+
+impl IfNoneMatch {
+    /// Whether `etag` is listed, using the weak comparison. A match means the client's copy is
+    /// current, so a `GET` can be answered with `304 Not Modified`.
+    pub fn matches(&self, etag: &ETag) -> bool {
+        match self {
+            IfNoneMatch::Any => true,
+            IfNoneMatch::Tags(tags) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        }
+    }
+}
+
+impl TypedHeader for IfNoneMatch {
+    fn name() -> &'static HeaderName {
+        &header::IF_NONE_MATCH
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        if value.trim() == "*" {
+            return Ok(IfNoneMatch::Any);
+        }
+        parse_tags::<Self>(value).map(IfNoneMatch::Tags)
+    }
+
+    fn to_value(&self) -> String {
+        match self {
+            IfNoneMatch::Any => "*".into(),
+            IfNoneMatch::Tags(tags) => render_tags(tags),
+        }
+    }
+}
+
+/// The `If-Match` header, listing the entity tags of the representations a request may act on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `*`, matching any current representation.
+    Any,
+    Tags(Vec<ETag>),
+}
+
+impl IfMatch {
+    /// Whether `etag` is listed, using the strong comparison. Without a match, the request must
+    /// fail with `412 Precondition Failed`.
+    pub fn matches(&self, etag: &ETag) -> bool {
+        match self {
+            IfMatch::Any => true,
+            IfMatch::Tags(tags) => tags.iter().any(|tag| tag.strong_eq(etag)),
+        }
+    }
+}
+
+impl TypedHeader for IfMatch {
+    fn name() -> &'static HeaderName {
+        &header::IF_MATCH
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        if value.trim() == "*" {
+            return Ok(IfMatch::Any);
+        }
+        parse_tags::<Self>(value).map(IfMatch::Tags)
+    }
+
+    fn to_value(&self) -> String {
+        match self {
+            IfMatch::Any => "*".into(),
+            IfMatch::Tags(tags) => render_tags(tags),
+        }
+    }
+}
+
+fn parse_tags<H: TypedHeader>(value: &str) -> Result<Vec<ETag>> {
+    split_quoted(value, ',')
+        .into_iter()
+        .map(|tag| ETag::parse_tag(tag).ok_or_else(|| invalid::<H>(value)))
+        .collect()
+}
+
+fn render_tags(tags: &[ETag]) -> String {
+    tags.iter().map(ETag::render).collect::<Vec<_>>().join(", ")
+}
+
+/// The `Authorization` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Authorization {
+    /// `Basic` credentials.
+    Basic { username: String, password: String },
+    /// A `Bearer` token.
+    Bearer(String),
+    /// Credentials using any other scheme, kept as sent.
+    Other { scheme: String, credentials: String },
+}
+
+impl Authorization {
+    pub fn basic(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self::Basic {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Self::Bearer(token.into())
+    }
+
+    /// The bearer token, if these are `Bearer` credentials.
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            Self::Bearer(token) => Some(token),
+            _ => None,
+        }
+    }
+}
+
+impl TypedHeader for Authorization {
+    fn name() -> &'static HeaderName {
+        &header::AUTHORIZATION
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let (scheme, credentials) = value
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| invalid::<Self>(value))?;
+        let credentials = credentials.trim();
+        if !is_token(scheme) || credentials.is_empty() {
+            return Err(invalid::<Self>(value));
+        }
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = base64::decode(credentials)
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| invalid::<Self>(value))?;
+            let (username, password) = decoded
+                .split_once(':')
+                .ok_or_else(|| invalid::<Self>(value))?;
+            Ok(Self::basic(username, password))
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            Ok(Self::bearer(credentials))
+        } else {
+            Ok(Self::Other {
+                scheme: scheme.to_string(),
+                credentials: credentials.to_string(),
+            })
+        }
+    }
+
+    fn to_value(&self) -> String {
+        match self {
+            Self::Basic { username, password } => {
+                format!(
+                    "Basic {}",
+                    base64::encode(format!("{username}:{password}").as_bytes())
+                )
+            }
+            Self::Bearer(token) => format!("Bearer {token}"),
+            Self::Other {
+                scheme,
+                credentials,
+            } => format!("{scheme} {credentials}"),
+        }
+    }
+}
+
+/// A media range listed in the [`Accept`] header, such as `text/*;q=0.8`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaRange {
+    essence: String,
+    params: Vec<(String, String)>,
+    quality: u16,
+}
+
+impl MediaRange {
+    /// A media range with a quality of 1.
+    ///
+    /// # Panics
+    ///
+    /// If `essence` isn't a valid `type/subtype` pair.
+    pub fn new(essence: &str) -> Self {
+        let essence =
+            parse_essence(essence).unwrap_or_else(|| panic!("invalid media range `{essence}`"));
+        Self {
+            essence,
+            params: Vec::new(),
+            quality: 1000,
+        }
+    }
+
+    /// Set the quality, between 0 and 1, rounded to three decimals.
+    pub fn with_q(mut self, q: f32) -> Self {
+        self.quality = (q.clamp(0.0, 1.0) * 1000.0).round() as u16;
+        self
+    }
+
+    /// The media range without its parameters, in lowercase, such as `text/*`.
+    pub fn essence(&self) -> &str {
+        &self.essence
+    }
+
+    /// The value of the parameter named `name`, which is case-insensitive.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The quality, between 0 (not acceptable) and 1.
+    pub fn q(&self) -> f32 {
+        self.quality as f32 / 1000.0
+    }
+
+    /// How closely this range matches the media type `essence`, from 3 for an exact match to 1
+    /// for `*/*`, or `None` if it doesn't match.
+    fn specificity(&self, essence: &str) -> Option<u8> {
+        if self.essence == "*/*" {
+            return Some(1);
+        }
+        if self.essence.eq_ignore_ascii_case(essence) {
+            return Some(3);
+        }
+        let (ty, _) = essence.split_once('/')?;
+        let range_ty = self.essence.strip_suffix("/*")?;
+        range_ty.eq_ignore_ascii_case(ty).then_some(2)
+    }
+}
+
+fn parse_quality(value: &str) -> Option<u16> {
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let frac = format!("{frac:0<3}").parse::<u16>().ok()?;
+    match int {
+        "0" => Some(frac),
+        "1" if frac == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+/// The `Accept` header, listing the media types the client prefers.
+///
+/// ```no_run
+/// # use worker::{headers::Accept, *};
+/// # fn main() -> Result<()> {
+/// # let req: Request = unimplemented!();
+/// let accept = req.headers().header::<Accept>()?.unwrap_or_default();
+/// match accept.negotiate(&["application/json", "text/html"]) {
+///     Some("text/html") => Response::from_html("<p>Hello</p>"),
+///     Some(_) => Response::from_json(&"Hello"),
+///     None => Response::error("Not Acceptable", 406),
+/// };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accept {
+    ranges: Vec<MediaRange>,
+}
+
+impl Accept {
+    pub fn new(ranges: impl IntoIterator<Item = MediaRange>) -> Self {
+        Self {
+            ranges: ranges.into_iter().collect(),
+        }
+    }
+
+    /// The listed media ranges, most preferred first: by quality, then from the most to the
+    /// least specific, then in the order they were sent.
+    pub fn ranges(&self) -> Vec<&MediaRange> {
+        let mut ranges = self.ranges.iter().collect::<Vec<_>>();
+        ranges.sort_by_key(|range| {
+            let specificity = match range.essence.as_str() {
+                "*/*" => 0,
+                e if e.ends_with("/*") => 1,
+                _ => 2 + range.params.len(),
+            };
+            std::cmp::Reverse((range.quality, specificity))
+        });
+        ranges
+    }
+
+    /// The quality the client gives to the media type `essence`, from the most specific range
+    /// matching it. Every type is acceptable when the header lists no ranges.
+    pub fn quality(&self, essence: &str) -> f32 {
+        if self.ranges.is_empty() {
+            return 1.0;
+        }
+        self.ranges
+            .iter()
+            .filter_map(|range| Some((range.specificity(essence)?, range.quality)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, quality)| quality as f32 / 1000.0)
+    }
+
+    /// Pick the media type the client prefers among `available`, or `None` if none of them is
+    /// acceptable. Ties go to the type listed first in `available`.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        let mut best = None;
+        for essence in available {
+            let quality = self.quality(essence);
+            if quality > 0.0 && best.map_or(true, |(_, q)| quality > q) {
+                best = Some((*essence, quality));
+            }
+        }
+        best.map(|(essence, _)| essence)
+    }
+}
+
+impl TypedHeader for Accept {
+    fn name() -> &'static HeaderName {
+        &header::ACCEPT
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let ranges = split_quoted(value, ',')
+            .into_iter()
+            .map(|range| {
+                let parts = split_quoted(range, ';');
+                let (essence, params) = parts.split_first()?;
+                let mut range = MediaRange {
+                    essence: parse_essence(essence)?,
+                    params: parse_params(params)?,
+                    quality: 1000,
+                };
+                if let Some(i) = range.params.iter().position(|(name, _)| name == "q") {
+                    let (_, q) = range.params.remove(i);
+                    range.quality = parse_quality(&q)?;
+                }
+                Some(range)
+            })
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid::<Self>(value))?;
+        Ok(Self { ranges })
+    }
+
+    fn to_value(&self) -> String {
+        self.ranges
+            .iter()
+            .map(|range| {
+                let mut out = range.essence.clone();
+                render_params(&mut out, &range.params);
+                if range.quality != 1000 {
+                    let q = format!("{:.3}", range.q());
+                    out.push_str(";q=");
+                    out.push_str(q.trim_end_matches('0').trim_end_matches('.'));
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A byte range listed in the [`Range`] header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRangeSpec {
+    /// `start-end`, both inclusive.
+    FromTo(u64, u64),
+    /// `start-`, up to the end.
+    From(u64),
+    /// `-length`, the last `length` bytes.
+    Suffix(u64),
+}
+
+impl ByteRangeSpec {
+    /// The first and last byte (inclusive) of this range within a representation of `size`
+    /// bytes, or `None` if it can't be satisfied.
+    pub fn resolve(self, size: u64) -> Option<(u64, u64)> {
+        match self {
+            Self::FromTo(start, end) if start < size => Some((start, end.min(size - 1))),
+            Self::From(start) if start < size => Some((start, size - 1)),
+            Self::Suffix(suffix) if suffix > 0 && size > 0 => {
+                Some((size.saturating_sub(suffix), size - 1))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse(spec: &str) -> Option<Self> {
+        let number = |s: &str| -> Option<u64> {
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            s.parse().ok()
+        };
+        let (start, end) = spec.split_once('-')?;
+        Some(match (start.trim(), end.trim()) {
+            ("", suffix) => Self::Suffix(number(suffix)?),
+            (start, "") => Self::From(number(start)?),
+            (start, end) => {
+                let (start, end) = (number(start)?, number(end)?);
+                if end < start {
+                    return None;
+                }
+                Self::FromTo(start, end)
+            }
+        })
+    }
+}
+
+/// The `Range` header, requesting parts of a representation in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range {
+    ranges: Vec<ByteRangeSpec>,
+}
+
+impl Range {
+    /// Request a single byte range.
+    pub fn bytes(spec: ByteRangeSpec) -> Self {
+        Self { ranges: vec![spec] }
+    }
+
+    pub fn ranges(&self) -> &[ByteRangeSpec] {
+        &self.ranges
+    }
+}
+
+impl TypedHeader for Range {
+    fn name() -> &'static HeaderName {
+        &header::RANGE
+    }
+
+    /// Fails for units other than `bytes`, which servers should ignore.
+    fn parse(value: &str) -> Result<Self> {
+        let (unit, ranges) = value
+            .split_once('=')
+            .ok_or_else(|| invalid::<Self>(value))?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") {
+            return Err(invalid::<Self>(value));
+        }
+        let ranges = split_quoted(ranges, ',')
+            .into_iter()
+            .map(ByteRangeSpec::parse)
+            .collect::<Option<Vec<_>>>()
+            .filter(|ranges| !ranges.is_empty())
+            .ok_or_else(|| invalid::<Self>(value))?;
+        Ok(Self { ranges })
+    }
+
+    fn to_value(&self) -> String {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|spec| match spec {
+                ByteRangeSpec::FromTo(start, end) => format!("{start}-{end}"),
+                ByteRangeSpec::From(start) => format!("{start}-"),
+                ByteRangeSpec::Suffix(suffix) => format!("-{suffix}"),
+            })
+            .collect::<Vec<_>>();
+        format!("bytes={}", ranges.join(", "))
+    }
+}
+
+/// The `Vary` header, listing the request headers a response depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Vary {
+    /// `*`, the response depends on more than request headers.
+    Any,
+    Headers(Vec<String>),
+}
+
+impl Vary {
+    /// Whether the response varies on the request header `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        match self {
+            Vary::Any => true,
+            Vary::Headers(names) => names.iter().any(|n| n.eq_ignore_ascii_case(name)),
+        }
+    }
+}
+
+impl TypedHeader for Vary {
+    fn name() -> &'static HeaderName {
+        &header::VARY
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let names = split_quoted(value, ',');
+        if names.contains(&"*") {
+            return Ok(Vary::Any);
+        }
+        if !names.iter().all(|name| is_token(name)) {
+            return Err(invalid::<Self>(value));
+        }
+        Ok(Vary::Headers(names.into_iter().map(Into::into).collect()))
+    }
+
+    fn to_value(&self) -> String {
+        match self {
+            Vary::Any => "*".into(),
+            Vary::Headers(names) => names.join(", "),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+macro_rules! impl_header {
+    ($($ty:ty),*) => {$(
+        impl ::headers::Header for $ty {
+            fn name() -> &'static HeaderName {
+                <$ty as TypedHeader>::name()
+            }
+
+            fn decode<'i, I>(values: &mut I) -> std::result::Result<Self, ::headers::Error>
+            where
+                I: Iterator<Item = &'i http::HeaderValue>,
+            {
+                let values = values
+                    .map(|value| value.to_str())
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|_| ::headers::Error::invalid())?;
+                if values.is_empty() {
+                    return Err(::headers::Error::invalid());
+                }
+                <$ty as TypedHeader>::parse(&values.join(", "))
+                    .map_err(|_| ::headers::Error::invalid())
+            }
+
+            fn encode<E: Extend<http::HeaderValue>>(&self, values: &mut E) {
+                if let Ok(value) = http::HeaderValue::from_str(&self.to_value()) {
+                    values.extend(std::iter::once(value));
+                }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "http")]
+impl_header!(
+    ContentType,
+    CacheControl,
+    ETag,
+    IfMatch,
+    IfNoneMatch,
+    Authorization,
+    Accept,
+    Range,
+    Vary
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip<H: TypedHeader + std::fmt::Debug + PartialEq>(value: &str) -> H {
+        let header = H::parse(value).expect("parse header");
+        assert_eq!(
+            H::parse(&header.to_value()).expect("reparse header"),
+            header
+        );
+        header
+    }
+
+    #[test]
+    fn content_type() {
+        let ct: ContentType = round_trip("Text/HTML; Charset=\"utf-8\"; boundary=\"a;b\"");
+        assert_eq!(ct.essence(), "text/html");
+        assert_eq!(ct.charset(), Some("utf-8"));
+        assert_eq!(ct.param("boundary"), Some("a;b"));
+        assert_eq!(ct.to_value(), "text/html; charset=utf-8; boundary=\"a;b\"");
+        assert_eq!(ContentType::html().to_value(), "text/html; charset=utf-8");
+        assert!(ContentType::parse("text").is_err());
+        assert!(ContentType::parse("text/html; charset").is_err());
+    }
+
+    #[test]
+    fn cache_control() {
+        let cc: CacheControl = round_trip(
+            "public, max-age=60, s-maxage=\"120\", no-cache=\"Set-Cookie\", foo=bar, baz",
+        );
+        assert!(cc.public && cc.no_cache);
+        assert_eq!(cc.max_age, Some(Duration::from_secs(60)));
+        assert_eq!(cc.s_max_age, Some(Duration::from_secs(120)));
+        assert_eq!(
+            cc.extensions,
+            [("foo".into(), Some("bar".into())), ("baz".into(), None)]
+        );
+        assert_eq!(
+            cc.to_value(),
+            "public, no-cache, max-age=60, s-maxage=120, foo=bar, baz"
+        );
+        assert!(CacheControl::parse("max-age=soon").is_err());
+        assert!(CacheControl::parse("max-age").is_err());
+    }
+
+    #[test]
+    fn etags() {
+        let strong: ETag = round_trip("\"abc\"");
+        let weak: ETag = round_trip("W/\"abc\"");
+        assert!(weak.is_weak());
+        assert!(strong.weak_eq(&weak));
+        assert!(!strong.strong_eq(&weak));
+        assert!(ETag::parse("abc").is_err());
+
+        let inm: IfNoneMatch = round_trip("\"x\", W/\"abc\"");
+        assert!(inm.matches(&strong));
+        assert!(!inm.matches(&ETag::strong("y")));
+        assert!(IfNoneMatch::parse("*").unwrap().matches(&strong));
+
+        let im: IfMatch = round_trip("\"x\", W/\"abc\"");
+        assert!(!im.matches(&strong));
+        assert!(IfMatch::parse("\"abc\"").unwrap().matches(&strong));
+        assert!(IfMatch::parse("*").unwrap().matches(&weak));
+    }
+
+    #[test]
+    fn authorization() {
+        let basic: Authorization = round_trip("Basic dXNlcjpwYTpzcw==");
+        assert_eq!(basic, Authorization::basic("user", "pa:ss"));
+        let bearer: Authorization = round_trip("bearer abc.def");
+        assert_eq!(bearer.token(), Some("abc.def"));
+        assert_eq!(bearer.to_value(), "Bearer abc.def");
+        let other: Authorization = round_trip("Digest realm=\"x\"");
+        assert!(matches!(other, Authorization::Other { .. }));
+        assert!(Authorization::parse("Basic !!!").is_err());
+        assert!(Authorization::parse("Bearer").is_err());
+    }
+
+    #[test]
+    fn accept() {
+        let accept: Accept = round_trip("text/*;q=0.5, application/json, */*;q=0.1, text/csv;q=0");
+        assert_eq!(
+            accept
+                .ranges()
+                .iter()
+                .map(|r| r.essence())
+                .collect::<Vec<_>>(),
+            ["application/json", "text/*", "*/*", "text/csv"]
+        );
+        assert_eq!(accept.quality("text/html"), 0.5);
+        assert_eq!(accept.quality("text/csv"), 0.0);
+        assert_eq!(accept.quality("image/png"), 0.1);
+        assert_eq!(
+            accept.negotiate(&["text/html", "application/json"]),
+            Some("application/json")
+        );
+        assert_eq!(accept.negotiate(&["text/csv"]), None);
+        assert_eq!(
+            Accept::default().negotiate(&["text/html", "text/plain"]),
+            Some("text/html")
+        );
+        assert_eq!(
+            accept.to_value(),
+            "text/*;q=0.5, application/json, */*;q=0.1, text/csv;q=0"
+        );
+        assert!(Accept::parse("text/html;q=2").is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        let range: Range = round_trip("bytes=0-499, 500-, -100");
+        assert_eq!(
+            range.ranges(),
+            [
+                ByteRangeSpec::FromTo(0, 499),
+                ByteRangeSpec::From(500),
+                ByteRangeSpec::Suffix(100)
+            ]
+        );
+        assert!(Range::parse("items=0-1").is_err());
+        assert!(Range::parse("bytes=5-1").is_err());
+        assert!(Range::parse("bytes=-").is_err());
+        assert!(Range::parse("bytes=+1-2").is_err());
+        assert!(Range::parse("bytes=").is_err());
+    }
+
+    #[test]
+    fn resolves_ranges_against_size() {
+        assert_eq!(ByteRangeSpec::FromTo(0, 499).resolve(1000), Some((0, 499)));
+        assert_eq!(
+            ByteRangeSpec::FromTo(900, 2000).resolve(1000),
+            Some((900, 999))
+        );
+        assert_eq!(ByteRangeSpec::From(10).resolve(1000), Some((10, 999)));
+        assert_eq!(ByteRangeSpec::Suffix(100).resolve(1000), Some((900, 999)));
+        assert_eq!(ByteRangeSpec::Suffix(5000).resolve(1000), Some((0, 999)));
+
+        assert_eq!(ByteRangeSpec::From(1000).resolve(1000), None);
+        assert_eq!(ByteRangeSpec::Suffix(0).resolve(1000), None);
+        assert_eq!(ByteRangeSpec::Suffix(10).resolve(0), None);
+    }
+
+    #[test]
+    fn vary() {
+        let vary: Vary = round_trip("Accept-Encoding, Origin");
+        assert!(vary.contains("accept-encoding"));
+        assert!(!vary.contains("cookie"));
+        assert_eq!(Vary::parse("origin, *").unwrap(), Vary::Any);
+    }
+}
@@ -0,0 +1,694 @@
+//! Streaming HTML transformation with the Workers
+//! [`HTMLRewriter`](https://developers.cloudflare.com/workers/runtime-apis/html-rewriter/).
+//!
+//! ```no_run
+//! use worker::{html_rewriter::ContentType, *};
+//!
+//! async fn rewrite(res: Response) -> Result<Response> {
+//!     HtmlRewriter::new()
+//!         .on_element("a[href^='http']", |el| {
+//!             el.set_attribute("rel", "noopener")?;
+//!             Ok(())
+//!         })
+//!         .on_element_async("img[data-src]", |el| async move {
+//!             if let Some(src) = el.get_attribute("data-src") {
+//!                 el.set_attribute("src", &src)?;
+//!             }
+//!             Ok(())
+//!         })
+//!         .on_document_end(|end| end.append("<!-- rewritten -->", ContentType::Html))
+//!         .transform(res)
+//! }
+//! ```
+
+use std::{cell::RefCell, future::Future, panic::AssertUnwindSafe, rc::Rc};
+
+use js_sys::{futures::future_to_promise, Array, JsString, Object as JsObject, Reflect};
+use wasm_bindgen::{convert::FromWasmAbi, prelude::Closure, JsCast, JsValue};
+use worker_sys::{
+    Comment as EdgeComment, Doctype as EdgeDoctype, DocumentEnd as EdgeDocumentEnd,
+    Element as EdgeElement, EndTag as EdgeEndTag, HtmlRewriter as EdgeHtmlRewriter,
+    TextChunk as EdgeTextChunk,
+};
+
+use crate::{Response, Result};
+
+/// Rewrites the HTML body of a [`Response`] as it streams through the worker, calling handlers
+/// registered for the elements matching CSS selectors and for the document as a whole.
+///
+/// Handlers run in the order they were registered. An `Err` returned by a handler aborts the
+/// transformation and errors the body stream of the transformed response.
+pub struct HtmlRewriter {
+    handlers: Vec<(Option<String>, JsObject)>,
+}
+
+impl core::fmt::Debug for HtmlRewriter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HtmlRewriter")
+            .field(
+                "selectors",
+                &self
+                    .handlers
+                    .iter()
+                    .map(|(selector, _)| selector.as_deref().unwrap_or("<document>"))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl HtmlRewriter {
+    pub fn new() -> Self {
+        Self {
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Register handlers for the elements matching `selector` and for their content.
+    pub fn on(mut self, selector: &str, handlers: impl ElementContentHandlers + 'static) -> Self {
+        let handlers = Rc::new(RefCell::new(handlers));
+        let object = JsObject::new();
+        set(&object, "element", {
+            let handlers = handlers.clone();
+            handler(move |el: &mut Element| handlers.borrow_mut().element(el))
+        });
+        set(&object, "comments", {
+            let handlers = handlers.clone();
+            handler(move |comment: &mut Comment| handlers.borrow_mut().comments(comment))
+        });
+        set(
+            &object,
+            "text",
+            handler(move |text: &mut TextChunk| handlers.borrow_mut().text(text)),
+        );
+        self.handlers.push((Some(selector.into()), object));
+        self
+    }
+
+    /// Register handlers for the document as a whole.
+    pub fn on_document(mut self, handlers: impl DocumentContentHandlers + 'static) -> Self {
+        let handlers = Rc::new(RefCell::new(handlers));
+        let object = JsObject::new();
+        set(&object, "doctype", {
+            let handlers = handlers.clone();
+            handler(move |doctype: &mut Doctype| handlers.borrow_mut().doctype(doctype))
+        });
+        set(&object, "comments", {
+            let handlers = handlers.clone();
+            handler(move |comment: &mut Comment| handlers.borrow_mut().comments(comment))
+        });
+        set(&object, "text", {
+            let handlers = handlers.clone();
+            handler(move |text: &mut TextChunk| handlers.borrow_mut().text(text))
+        });
+        set(
+            &object,
+            "end",
+            handler(move |end: &mut DocumentEnd| handlers.borrow_mut().end(end)),
+        );
+        self.handlers.push((None, object));
+        self
+    }
+
+    /// Register async handlers for the elements matching `selector` and for their content. The
+    /// transformation pauses until each handler's future resolves.
+    pub fn on_async(
+        mut self,
+        selector: &str,
+        handlers: impl AsyncElementContentHandlers + 'static,
+    ) -> Self {
+        let handlers = Rc::new(handlers);
+        let object = JsObject::new();
+        set(&object, "element", {
+            let handlers = handlers.clone();
+            async_handler(move |el: Element| {
+                let handlers = handlers.clone();
+                async move { handlers.element(el).await }
+            })
+        });
+        set(&object, "comments", {
+            let handlers = handlers.clone();
+            async_handler(move |comment: Comment| {
+                let handlers = handlers.clone();
+                async move { handlers.comments(comment).await }
+            })
+        });
+        set(
+            &object,
+            "text",
+            async_handler(move |text: TextChunk| {
+                let handlers = handlers.clone();
+                async move { handlers.text(text).await }
+            }),
+        );
+        self.handlers.push((Some(selector.into()), object));
+        self
+    }
+
+    /// Register async handlers for the document as a whole. The transformation pauses until
+    /// each handler's future resolves.
+    pub fn on_document_async(
+        mut self,
+        handlers: impl AsyncDocumentContentHandlers + 'static,
+    ) -> Self {
+        let handlers = Rc::new(handlers);
+        let object = JsObject::new();
+        set(&object, "doctype", {
+            let handlers = handlers.clone();
+            async_handler(move |doctype: Doctype| {
+                let handlers = handlers.clone();
+                async move { handlers.doctype(doctype).await }
+            })
+        });
+        set(&object, "comments", {
+            let handlers = handlers.clone();
+            async_handler(move |comment: Comment| {
+                let handlers = handlers.clone();
+                async move { handlers.comments(comment).await }
+            })
+        });
+        set(&object, "text", {
+            let handlers = handlers.clone();
+            async_handler(move |text: TextChunk| {
+                let handlers = handlers.clone();
+                async move { handlers.text(text).await }
+            })
+        });
+        set(
+            &object,
+            "end",
+            async_handler(move |end: DocumentEnd| {
+                let handlers = handlers.clone();
+                async move { handlers.end(end).await }
+            }),
+        );
+        self.handlers.push((None, object));
+        self
+    }
+
+    /// Call `f` for every element matching `selector`.
+    pub fn on_element(
+        self,
+        selector: &str,
+        f: impl FnMut(&mut Element) -> Result<()> + 'static,
+    ) -> Self {
+        self.push(Some(selector), "element", handler(f))
+    }
+
+    /// Call `f` for every element matching `selector`, pausing the transformation until the
+    /// returned future resolves.
+    pub fn on_element_async<Fut>(self, selector: &str, f: impl Fn(Element) -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        self.push(Some(selector), "element", async_handler(f))
+    }
+
+    /// Call `f` for every comment inside the elements matching `selector`.
+    pub fn on_comment(
+        self,
+        selector: &str,
+        f: impl FnMut(&mut Comment) -> Result<()> + 'static,
+    ) -> Self {
+        self.push(Some(selector), "comments", handler(f))
+    }
+
+    /// Call `f` for every comment inside the elements matching `selector`, pausing the
+    /// transformation until the returned future resolves.
+    pub fn on_comment_async<Fut>(self, selector: &str, f: impl Fn(Comment) -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        self.push(Some(selector), "comments", async_handler(f))
+    }
+
+    /// Call `f` for every chunk of text inside the elements matching `selector`.
+    pub fn on_text(
+        self,
+        selector: &str,
+        f: impl FnMut(&mut TextChunk) -> Result<()> + 'static,
+    ) -> Self {
+        self.push(Some(selector), "text", handler(f))
+    }
+
+    /// Call `f` for every chunk of text inside the elements matching `selector`, pausing the
+    /// transformation until the returned future resolves.
+    pub fn on_text_async<Fut>(self, selector: &str, f: impl Fn(TextChunk) -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        self.push(Some(selector), "text", async_handler(f))
+    }
+
+    /// Call `f` for the document's doctype.
+    pub fn on_doctype(self, f: impl FnMut(&mut Doctype) -> Result<()> + 'static) -> Self {
+        self.push(None, "doctype", handler(f))
+    }
+
+    /// Call `f` for the document's doctype, pausing the transformation until the returned future
+    /// resolves.
+    pub fn on_doctype_async<Fut>(self, f: impl Fn(Doctype) -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        self.push(None, "doctype", async_handler(f))
+    }
+
+    /// Call `f` once the end of the document is reached.
+    pub fn on_document_end(self, f: impl FnMut(&mut DocumentEnd) -> Result<()> + 'static) -> Self {
+        self.push(None, "end", handler(f))
+    }
+
+    /// Call `f` once the end of the document is reached, finishing the transformation once the
+    /// returned future resolves.
+    pub fn on_document_end_async<Fut>(self, f: impl Fn(DocumentEnd) -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        self.push(None, "end", async_handler(f))
+    }
+
+    fn push(mut self, selector: Option<&str>, kind: &str, handler: JsValue) -> Self {
+        let object = JsObject::new();
+        set(&object, kind, handler);
+        self.handlers.push((selector.map(Into::into), object));
+        self
+    }
+
+    /// Transform the body of `response`. The returned response streams the rewritten body as the
+    /// original body is read, and otherwise has the same status and headers.
+    pub fn transform(self, response: Response) -> Result<Response> {
+        let rewriter = EdgeHtmlRewriter::new()?;
+        for (selector, handlers) in &self.handlers {
+            match selector {
+                Some(selector) => rewriter.on(selector, handlers)?,
+                None => rewriter.on_document(handlers)?,
+            };
+        }
+
+        let response: web_sys::Response = response.into();
+        Ok(rewriter.transform(&response)?.into())
+    }
+}
+
+/// Handlers for the elements matching a selector, registered with [`HtmlRewriter::on`].
+pub trait ElementContentHandlers {
+    fn element(&mut self, _element: &mut Element) -> Result<()> {
+        Ok(())
+    }
+
+    fn comments(&mut self, _comment: &mut Comment) -> Result<()> {
+        Ok(())
+    }
+
+    fn text(&mut self, _text: &mut TextChunk) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Handlers for the whole document, registered with [`HtmlRewriter::on_document`].
+pub trait DocumentContentHandlers {
+    fn doctype(&mut self, _doctype: &mut Doctype) -> Result<()> {
+        Ok(())
+    }
+
+    fn comments(&mut self, _comment: &mut Comment) -> Result<()> {
+        Ok(())
+    }
+
+    fn text(&mut self, _text: &mut TextChunk) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(&mut self, _end: &mut DocumentEnd) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Async handlers for the elements matching a selector, registered with
+/// [`HtmlRewriter::on_async`]. They take `&self`, since the rewriter holds on to them while their
+/// futures run; use interior mutability for state.
+#[async_trait::async_trait(?Send)]
+pub trait AsyncElementContentHandlers {
+    async fn element(&self, _element: Element) -> Result<()> {
+        Ok(())
+    }
+
+    async fn comments(&self, _comment: Comment) -> Result<()> {
+        Ok(())
+    }
+
+    async fn text(&self, _text: TextChunk) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Async handlers for the whole document, registered with [`HtmlRewriter::on_document_async`].
+/// Like [`AsyncElementContentHandlers`], they take `&self`.
+#[async_trait::async_trait(?Send)]
+pub trait AsyncDocumentContentHandlers {
+    async fn doctype(&self, _doctype: Doctype) -> Result<()> {
+        Ok(())
+    }
+
+    async fn comments(&self, _comment: Comment) -> Result<()> {
+        Ok(())
+    }
+
+    async fn text(&self, _text: TextChunk) -> Result<()> {
+        Ok(())
+    }
+
+    async fn end(&self, _end: DocumentEnd) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn set(object: &JsObject, key: &str, value: JsValue) {
+    let res = Reflect::set(object, &JsString::from(key), &value);
+    debug_assert!(
+        res.is_ok(),
+        "setting properties should never fail on a plain object"
+    );
+}
+
+/// Wrap `f` in a JS function handing it the Rust wrapper of the rewriter's argument. Ownership
+/// of the closure moves to JS, which keeps it alive for as long as the rewriter may call it.
+fn handler<T: Wrapper>(mut f: impl FnMut(&mut T) -> Result<()> + 'static) -> JsValue {
+    let f = move |sys: T::Sys| f(&mut T::wrap(sys)).map_err(JsValue::from);
+    Closure::wrap_assert_unwind_safe(Box::new(f) as Box<dyn FnMut(T::Sys) -> Result<(), JsValue>>)
+        .into_js_value()
+}
+
+/// Like [`handler`], for closures returning a future. The rewriter waits for the returned promise
+/// before it continues.
+fn async_handler<T, Fut>(f: impl Fn(T) -> Fut + 'static) -> JsValue
+where
+    T: Wrapper,
+    Fut: Future<Output = Result<()>> + 'static,
+{
+    let f = move |sys: T::Sys| {
+        let fut = f(T::wrap(sys));
+        future_to_promise(AssertUnwindSafe(async move {
+            fut.await.map(|_| JsValue::UNDEFINED).map_err(JsValue::from)
+        }))
+    };
+    Closure::wrap_assert_unwind_safe(Box::new(f) as Box<dyn FnMut(T::Sys) -> js_sys::Promise>)
+        .into_js_value()
+}
+
+/// How content inserted into the document is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentType {
+    /// The content is escaped, and so shows up as text.
+    #[default]
+    Text,
+    /// The content is inserted as raw HTML.
+    Html,
+}
+
+impl ContentType {
+    fn options(self) -> JsValue {
+        let options = JsObject::new();
+        set(&options, "html", (self == ContentType::Html).into());
+        options.into()
+    }
+}
+
+/// A Rust wrapper of an argument passed by the rewriter to a handler.
+trait Wrapper {
+    type Sys: FromWasmAbi + 'static;
+
+    fn wrap(sys: Self::Sys) -> Self;
+}
+
+macro_rules! wrapper {
+    ($(#[$meta:meta])* $name:ident, $sys:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            inner: $sys,
+        }
+
+        impl Wrapper for $name {
+            type Sys = $sys;
+
+            fn wrap(inner: $sys) -> Self {
+                Self { inner }
+            }
+        }
+    };
+}
+
+wrapper!(
+    /// An element matched by a selector.
+    Element,
+    EdgeElement
+);
+wrapper!(
+    /// The end tag of an [`Element`], available to [`Element::on_end_tag`] handlers.
+    EndTag,
+    EdgeEndTag
+);
+wrapper!(
+    /// An HTML comment.
+    Comment,
+    EdgeComment
+);
+wrapper!(
+    /// A chunk of a text node. A single text node may be split over several chunks, the last of
+    /// which reports [`last_in_text_node`](TextChunk::last_in_text_node).
+    TextChunk,
+    EdgeTextChunk
+);
+wrapper!(
+    /// The document's doctype.
+    Doctype,
+    EdgeDoctype
+);
+wrapper!(
+    /// The end of the document, where content can be appended.
+    DocumentEnd,
+    EdgeDocumentEnd
+);
+
+impl Element {
+    /// The tag name, in lowercase.
+    pub fn tag_name(&self) -> String {
+        self.inner.tag_name()
+    }
+
+    pub fn set_tag_name(&self, name: &str) {
+        self.inner.set_tag_name(name)
+    }
+
+    pub fn namespace_uri(&self) -> String {
+        self.inner.namespace_uri()
+    }
+
+    /// The element's attributes as `(name, value)` pairs.
+    pub fn attributes(&self) -> Result<Vec<(String, String)>> {
+        self.inner
+            .attributes()
+            .into_iter()
+            .map(|pair| {
+                let pair: Array = pair?.unchecked_into();
+                Ok((
+                    pair.get(0).as_string().unwrap_or_default(),
+                    pair.get(1).as_string().unwrap_or_default(),
+                ))
+            })
+            .collect()
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        self.inner.get_attribute(name)
+    }
+
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.inner.has_attribute(name)
+    }
+
+    pub fn set_attribute(&self, name: &str, value: &str) -> Result<()> {
+        self.inner.set_attribute(name, value)?;
+        Ok(())
+    }
+
+    pub fn remove_attribute(&self, name: &str) {
+        self.inner.remove_attribute(name);
+    }
+
+    /// Insert `content` before the element's start tag.
+    pub fn before(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.before(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Insert `content` after the element's end tag.
+    pub fn after(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.after(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Insert `content` right after the element's start tag.
+    pub fn prepend(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.prepend(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Insert `content` right before the element's end tag.
+    pub fn append(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.append(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Replace the element and its content with `content`.
+    pub fn replace(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.replace(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Replace the content of the element with `content`.
+    pub fn set_inner_content(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner
+            .set_inner_content(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Remove the element and its content.
+    pub fn remove(&self) {
+        self.inner.remove();
+    }
+
+    /// Remove the element's tags, keeping its content.
+    pub fn remove_and_keep_content(&self) {
+        self.inner.remove_and_keep_content();
+    }
+
+    pub fn removed(&self) -> bool {
+        self.inner.removed()
+    }
+
+    /// Call `f` when the element's end tag is reached.
+    pub fn on_end_tag(&self, f: impl FnOnce(&mut EndTag) -> Result<()> + 'static) -> Result<()> {
+        let f = move |sys: EdgeEndTag| f(&mut EndTag::wrap(sys)).map_err(JsValue::from);
+        let f = Closure::once_assert_unwind_safe(f).into_js_value();
+        self.inner.on_end_tag(f.unchecked_ref())?;
+        Ok(())
+    }
+}
+
+impl EndTag {
+    pub fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    pub fn set_name(&self, name: &str) {
+        self.inner.set_name(name)
+    }
+
+    /// Insert `content` before the end tag.
+    pub fn before(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.before(content, &content_type.options())?;
+        Ok(())
+    }
+
+    /// Insert `content` after the end tag.
+    pub fn after(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.after(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn remove(&self) {
+        self.inner.remove();
+    }
+}
+
+impl Comment {
+    pub fn text(&self) -> String {
+        self.inner.text()
+    }
+
+    pub fn set_text(&self, text: &str) {
+        self.inner.set_text(text)
+    }
+
+    pub fn before(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.before(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn after(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.after(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn replace(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.replace(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn remove(&self) {
+        self.inner.remove();
+    }
+
+    pub fn removed(&self) -> bool {
+        self.inner.removed()
+    }
+}
+
+impl TextChunk {
+    pub fn text(&self) -> String {
+        self.inner.text()
+    }
+
+    /// Whether this is the last chunk of its text node, which may be empty.
+    pub fn last_in_text_node(&self) -> bool {
+        self.inner.last_in_text_node()
+    }
+
+    pub fn before(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.before(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn after(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.after(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn replace(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.replace(content, &content_type.options())?;
+        Ok(())
+    }
+
+    pub fn remove(&self) {
+        self.inner.remove();
+    }
+
+    pub fn removed(&self) -> bool {
+        self.inner.removed()
+    }
+}
+
+impl Doctype {
+    pub fn name(&self) -> Option<String> {
+        self.inner.name()
+    }
+
+    pub fn public_id(&self) -> Option<String> {
+        self.inner.public_id()
+    }
+
+    pub fn system_id(&self) -> Option<String> {
+        self.inner.system_id()
+    }
+}
+
+impl DocumentEnd {
+    /// Insert `content` at the end of the document.
+    pub fn append(&self, content: &str, content_type: ContentType) -> Result<()> {
+        self.inner.append(content, &content_type.options())?;
+        Ok(())
+    }
+}
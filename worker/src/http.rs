@@ -43,6 +43,20 @@ impl Method {
             Method::Report,
         ]
     }
+
+    /// Whether repeating a request with this method has the same effect as sending it once, as
+    /// defined by RFC 9110, which makes it safe to retry.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            Method::Head
+                | Method::Get
+                | Method::Put
+                | Method::Delete
+                | Method::Options
+                | Method::Trace
+        )
+    }
 }
 
 impl From<String> for Method {
@@ -0,0 +1,324 @@
+//! Interception of outbound requests.
+//!
+//! Interceptors registered with [`register`] see every request sent with [`Fetch`](crate::Fetch),
+//! a [`Fetcher`](crate::Fetcher) or a [`Client`](crate::Client), in registration order. They can
+//! observe or rewrite the request and the response, or answer without sending the request at
+//! all, which lets tests run code that makes subrequests against canned responses with
+//! [`MockFetch`].
+//!
+//! Interceptors apply to the whole isolate, including the other requests it handles
+//! concurrently, so register them once when the Worker starts rather than in a handler:
+//!
+//! ```no_run
+//! use worker::{intercept::FetchInterceptor, *};
+//!
+//! struct TraceId(String);
+//!
+//! #[async_trait::async_trait(?Send)]
+//! impl FetchInterceptor for TraceId {
+//!     async fn before(&self, req: &mut Request) -> Result<Option<Response>> {
+//!         req.headers_mut()?.set("X-Trace-Id", &self.0)?;
+//!         Ok(None)
+//!     }
+//! }
+//!
+//! #[event(start)]
+//! fn start() {
+//!     intercept::register(TraceId("abc".into())).forget();
+//! }
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use futures_util::future::LocalBoxFuture;
+
+use crate::{Method, Request, Response, Result};
+
+pub(crate) type Endpoint<'s> = dyn 's + Fn(Request) -> LocalBoxFuture<'s, Result<Response>>;
+
+type Interceptors = Rc<[(u64, Rc<dyn FetchInterceptor>)]>;
+
+thread_local! {
+    static INTERCEPTORS: RefCell<Interceptors> = RefCell::new(Rc::new([]));
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Hooks that run around every outbound request.
+///
+/// Implement [`before`](FetchInterceptor::before) to inspect or modify the request and
+/// optionally answer it without sending it, and [`after`](FetchInterceptor::after) to
+/// post-process the response. For full control, override [`handle`](FetchInterceptor::handle)
+/// instead.
+#[async_trait::async_trait(?Send)]
+pub trait FetchInterceptor {
+    /// Runs before the request is sent. Returning `Ok(Some(response))` skips sending it (and any
+    /// interceptor registered after this one).
+    async fn before(&self, _req: &mut Request) -> Result<Option<Response>> {
+        Ok(None)
+    }
+
+    /// Runs with the response to the request. Not called if sending it failed.
+    async fn after(&self, res: Response) -> Result<Response> {
+        Ok(res)
+    }
+
+    /// Runs this interceptor around the rest of the chain, which is invoked with [`Next::run`].
+    /// The default implementation calls [`before`](FetchInterceptor::before) and
+    /// [`after`](FetchInterceptor::after).
+    async fn handle(&self, mut req: Request, next: Next<'_>) -> Result<Response> {
+        if let Some(res) = self.before(&mut req).await? {
+            return Ok(res);
+        }
+        let res = next.run(req).await?;
+        self.after(res).await
+    }
+}
+
+/// Register `interceptor` to run around every outbound request made from this isolate, after
+/// the interceptors already registered, until the returned [`Registration`] is dropped.
+pub fn register(interceptor: impl FetchInterceptor + 'static) -> Registration {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    INTERCEPTORS.with(|interceptors| {
+        let mut interceptors = interceptors.borrow_mut();
+        let mut list = interceptors.to_vec();
+        list.push((id, Rc::new(interceptor)));
+        *interceptors = list.into();
+    });
+    Registration { id: Some(id) }
+}
+
+/// Keeps an interceptor registered with [`register`] until it is dropped.
+#[derive(Debug)]
+#[must_use = "the interceptor is unregistered when the registration is dropped"]
+pub struct Registration {
+    id: Option<u64>,
+}
+
+impl Registration {
+    /// Keep the interceptor registered for the lifetime of the isolate.
+    pub fn forget(mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            INTERCEPTORS.with(|interceptors| {
+                let mut interceptors = interceptors.borrow_mut();
+                let list: Vec<_> = interceptors
+                    .iter()
+                    .filter(|(other, _)| *other != id)
+                    .cloned()
+                    .collect();
+                *interceptors = list.into();
+            });
+        }
+    }
+}
+
+/// Remove all registered interceptors.
+pub fn clear() {
+    INTERCEPTORS.with(|interceptors| *interceptors.borrow_mut() = Rc::new([]));
+}
+
+/// Whether any interceptor is registered, in which case requests are sent through [`run`].
+pub(crate) fn active() -> bool {
+    INTERCEPTORS.with(|interceptors| !interceptors.borrow().is_empty())
+}
+
+/// Send `req` through the registered interceptors, with `endpoint` actually sending it.
+pub(crate) async fn run<'s>(req: Request, endpoint: &'s Endpoint<'s>) -> Result<Response> {
+    let interceptors = INTERCEPTORS.with(|interceptors| interceptors.borrow().clone());
+    // Interceptors may modify the request, so give them one whose headers are mutable.
+    let req = req.into_mut()?;
+    Next {
+        interceptors,
+        index: 0,
+        endpoint,
+    }
+    .run(req)
+    .await
+}
+
+/// The remainder of an interceptor chain, ending with sending the request.
+pub struct Next<'s> {
+    interceptors: Interceptors,
+    index: usize,
+    endpoint: &'s Endpoint<'s>,
+}
+
+impl core::fmt::Debug for Next<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Next")
+            .field("remaining", &(self.interceptors.len() - self.index))
+            .finish()
+    }
+}
+
+impl Next<'_> {
+    /// Run the rest of the chain with the given request.
+    pub async fn run(self, req: Request) -> Result<Response> {
+        match self.interceptors.get(self.index).map(|(_, i)| i.clone()) {
+            Some(interceptor) => {
+                let next = Next {
+                    index: self.index + 1,
+                    ..self
+                };
+                interceptor.handle(req, next).await
+            }
+            None => (self.endpoint)(req).await,
+        }
+    }
+}
+
+type MockHandler = Box<dyn Fn(&Request) -> Result<Response>>;
+
+/// An interceptor answering requests with canned responses, for tests.
+///
+/// Requests are matched by method and URL, where a URL ending with `*` matches any URL starting
+/// with the rest of it. Requests that match no mock fail, unless
+/// [`passthrough`](MockFetch::passthrough) is set. The requests received are recorded and can be
+/// inspected with [`calls`](MockFetch::calls).
+///
+/// ```no_run
+/// # use std::rc::Rc;
+/// # use worker::{intercept::MockFetch, *};
+/// # async fn test() -> Result<()> {
+/// let mock = Rc::new(
+///     MockFetch::new().on(Method::Get, "https://api.example.com/users/*", |_| {
+///         Response::from_json(&serde_json::json!({ "name": "Ada" }))
+///     }),
+/// );
+/// let _registration = intercept::register(mock.clone());
+///
+/// let url = Url::parse("https://api.example.com/users/1")?;
+/// let mut res = Fetch::Url(url).send().await?;
+/// assert_eq!(mock.calls().len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct MockFetch {
+    mocks: Vec<(Method, String, MockHandler)>,
+    passthrough: bool,
+    calls: RefCell<Vec<(Method, String)>>,
+}
+
+impl core::fmt::Debug for MockFetch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MockFetch")
+            .field(
+                "mocks",
+                &self
+                    .mocks
+                    .iter()
+                    .map(|(method, url, _)| (method, url))
+                    .collect::<Vec<_>>(),
+            )
+            .field("passthrough", &self.passthrough)
+            .field("calls", &self.calls.borrow())
+            .finish()
+    }
+}
+
+impl MockFetch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests with `method` for `url` with the response returned by `handler`. Mocks
+    /// are tried in the order they were added.
+    pub fn on(
+        mut self,
+        method: Method,
+        url: impl Into<String>,
+        handler: impl Fn(&Request) -> Result<Response> + 'static,
+    ) -> Self {
+        self.mocks.push((method, url.into(), Box::new(handler)));
+        self
+    }
+
+    /// Send requests that match no mock, instead of failing them.
+    pub fn passthrough(mut self) -> Self {
+        self.passthrough = true;
+        self
+    }
+
+    /// The method and URL of every request received so far.
+    pub fn calls(&self) -> Vec<(Method, String)> {
+        self.calls.borrow().clone()
+    }
+}
+
+fn url_matches(pattern: &str, url: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => url.starts_with(prefix),
+        None => pattern == url,
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl FetchInterceptor for MockFetch {
+    async fn handle(&self, req: Request, next: Next<'_>) -> Result<Response> {
+        let method = req.method();
+        let url = req.url()?.to_string();
+        self.calls.borrow_mut().push((method.clone(), url.clone()));
+
+        let mock = self
+            .mocks
+            .iter()
+            .find(|(m, pattern, _)| *m == method && url_matches(pattern, &url));
+        match mock {
+            Some((_, _, handler)) => handler(&req),
+            None if self.passthrough => next.run(req).await,
+            None => Err(crate::Error::RustError(format!(
+                "no mock for {} {url}",
+                method.as_ref()
+            ))),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl<T: FetchInterceptor + ?Sized> FetchInterceptor for Rc<T> {
+    async fn handle(&self, req: Request, next: Next<'_>) -> Result<Response> {
+        (**self).handle(req, next).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_mock_urls() {
+        assert!(url_matches("https://a.test/x", "https://a.test/x"));
+        assert!(!url_matches("https://a.test/x", "https://a.test/x/y"));
+        assert!(url_matches("https://a.test/*", "https://a.test/x/y?z=1"));
+        assert!(!url_matches("https://a.test/*", "https://b.test/x"));
+    }
+
+    #[test]
+    fn registers_and_clears_interceptors() {
+        assert!(!active());
+        register(MockFetch::new()).forget();
+        assert!(active());
+        clear();
+        assert!(!active());
+    }
+
+    #[test]
+    fn unregisters_on_drop() {
+        let first = register(MockFetch::new());
+        let second = register(MockFetch::new().passthrough());
+        drop(first);
+        let remaining = INTERCEPTORS.with(|interceptors| interceptors.borrow().len());
+        assert_eq!(remaining, 1);
+        drop(second);
+        assert!(!active());
+    }
+}
@@ -4,7 +4,7 @@
 //! # Features
 //! ## `d1`
 //!
-//! Allows the use of [D1 bindings](crate::d1) and [`query!`](crate::query) macro.
+//! Allows the use of [D1 bindings](crate::d1) and [`query!`](crate::query!) macro.
 //!
 //!
 //! ## `queue`
@@ -19,6 +19,11 @@
 //! }
 //! ```
 //!
+//! ## `openapi`
+//!
+//! Allows documenting [`Router`] routes with [`RouteDoc`](crate::RouteDoc) and generating an
+//! OpenAPI 3 document from them with [`Router::openapi`] or [`Router::serve_openapi`].
+//!
 //! ## `http`
 //! `worker` `0.0.21` introduced an `http` feature flag which starts to replace custom types with widely used types from the [`http`](https://docs.rs/http/latest/http/) crate.
 //!
@@ -156,7 +161,9 @@ pub use wasm_bindgen_futures;
 pub use web_sys;
 
 pub use cf::{Cf, CfResponseProperties, TlsClientAuth};
-pub use worker_macros::{consume, durable_object, event, send};
+pub use worker_macros::{
+    consume, durable_object, embed_migrations, event, send, FromRow, ToParams,
+};
 #[doc(hidden)]
 pub use worker_sys;
 pub use worker_sys::{console_debug, console_error, console_log, console_warn};
@@ -164,10 +171,15 @@ pub use worker_sys::{BotManagement, JsDetection};
 
 pub use crate::abort::*;
 pub use crate::ai::*;
+pub use crate::alarm_scheduler::{AlarmDispatch, AlarmScheduler, Timer};
 pub use crate::analytics_engine::*;
+pub use crate::assets::Assets;
 pub use crate::cache::{Cache, CacheDeletionOutcome, CacheKey};
+pub use crate::client::{Client, ClientRequest, RetryPolicy};
+pub use crate::compression::CompressionFormat;
 pub use crate::container::*;
 pub use crate::context::Context;
+pub use crate::cookie::{Cookie, CookieJar, CookieKey, SameSite};
 pub use crate::cors::Cors;
 #[cfg(feature = "d1")]
 pub use crate::d1::*;
@@ -178,13 +190,21 @@ pub use crate::dynamic_dispatch::*;
 pub use crate::email::*;
 pub use crate::env::{Env, EnvBinding, Secret, Var};
 pub use crate::error::Error;
+pub use crate::extract::{Data, Form, FromRouteRequest, Json, Path, Query, RouteHandler};
 pub use crate::fetcher::Fetcher;
 pub use crate::formdata::*;
 pub use crate::global::Fetch;
 pub use crate::headers::Headers;
+pub use crate::html_rewriter::HtmlRewriter;
 pub use crate::http::Method;
 pub use crate::hyperdrive::*;
 pub use crate::kv::{KvError, KvStore};
+pub use crate::middleware::{Middleware, Next};
+pub use crate::migrations::Migration;
+pub use crate::multipart::{Multipart, MultipartPart};
+#[cfg(feature = "openapi")]
+pub use crate::openapi::RouteDoc;
+pub use crate::query::SqlExecutor;
 #[cfg(feature = "queue")]
 pub use crate::queue::*;
 pub use crate::r2::*;
@@ -192,10 +212,12 @@ pub use crate::rate_limit::{RateLimitOutcome, RateLimiter};
 pub use crate::request::{FromRequest, Request};
 pub use crate::request_init::*;
 pub use crate::response::{EncodeBody, IntoResponse, Response, ResponseBody, ResponseBuilder};
-pub use crate::router::{RouteContext, RouteParams, Router};
+pub use crate::router::{RouteContext, RouteInfo, RouteParams, Router};
+pub use crate::row::{FromRow, FromSqlValue, SqlRow, ToParams, ToSqlValue};
 pub use crate::schedule::*;
 pub use crate::secret_store::SecretStore;
 pub use crate::socket::*;
+pub use crate::sse::Sse;
 pub use crate::streams::*;
 pub use crate::version::*;
 pub use crate::websocket::*;
@@ -216,11 +238,17 @@ mod bindings;
 
 mod abort;
 mod ai;
+mod alarm_scheduler;
 mod analytics_engine;
+mod assets;
+mod base64;
 mod cache;
 mod cf;
+mod client;
+mod compression;
 mod container;
 mod context;
+mod cookie;
 mod cors;
 pub mod crypto;
 mod init;
@@ -235,13 +263,22 @@ mod dynamic_dispatch;
 pub mod email;
 mod env;
 mod error;
+mod extract;
 mod fetcher;
 mod formdata;
 mod global;
-mod headers;
+pub mod headers;
+pub mod html_rewriter;
 mod http;
 mod hyperdrive;
+pub mod intercept;
 pub mod kv;
+mod middleware;
+mod migrations;
+mod multipart;
+#[cfg(feature = "openapi")]
+mod openapi;
+pub mod query;
 #[cfg(feature = "queue")]
 mod queue;
 mod r2;
@@ -250,12 +287,15 @@ mod request;
 mod request_init;
 mod response;
 mod router;
+mod row;
 mod schedule;
 mod secret_store;
 pub mod send;
 pub mod signal;
 mod socket;
 mod sql;
+mod sql_stream;
+pub mod sse;
 mod streams;
 mod version;
 mod websocket;
@@ -295,3 +335,4 @@ pub type HttpRequest = ::http::Request<http::body::Body>;
 pub type HttpResponse = ::http::Response<http::body::Body>;
 
 pub use crate::sql::*;
+pub use crate::sql_stream::{SqlExportFormat, SqlRowStream};
@@ -0,0 +1,116 @@
+use std::rc::Rc;
+
+use futures_util::future::LocalBoxFuture;
+
+use crate::{request::Request, response::Response, router::RouteContext, Result};
+
+pub(crate) type Endpoint<'s, D> =
+    dyn 's + Fn(Request, RouteContext<D>) -> LocalBoxFuture<'s, Result<Response>>;
+
+/// Hooks that run around the handlers of a [`Router`](crate::Router).
+///
+/// Middleware can be attached to every route of a router with
+/// [`Router::middleware`](crate::Router::middleware) or to a subset of routes with
+/// [`Router::group`](crate::Router::group). Implement [`before`](Middleware::before) to inspect
+/// the request and optionally short-circuit with a `Response`, and [`after`](Middleware::after)
+/// to post-process the `Response` returned by the handler. For full control over when (or
+/// whether) the rest of the chain runs, override [`handle`](Middleware::handle) instead.
+///
+/// ```no_run
+/// use worker::*;
+///
+/// struct RequireToken;
+///
+/// #[async_trait::async_trait(?Send)]
+/// impl<D> Middleware<D> for RequireToken {
+///     async fn before(
+///         &self,
+///         req: &mut Request,
+///         _ctx: &mut RouteContext<D>,
+///     ) -> Result<Option<Response>> {
+///         match req.headers().get("Authorization")? {
+///             Some(_) => Ok(None),
+///             None => Response::error("Unauthorized", 401).map(Some),
+///         }
+///     }
+/// }
+/// ```
+#[async_trait::async_trait(?Send)]
+pub trait Middleware<D> {
+    /// Runs before the rest of the chain. Returning `Ok(Some(response))` skips the handler (and
+    /// any middleware after this one) and responds immediately.
+    async fn before(
+        &self,
+        _req: &mut Request,
+        _ctx: &mut RouteContext<D>,
+    ) -> Result<Option<Response>> {
+        Ok(None)
+    }
+
+    /// Runs with the `Response` produced by the rest of the chain. Not called if the handler
+    /// returned an `Err`.
+    async fn after(&self, res: Response) -> Result<Response> {
+        Ok(res)
+    }
+
+    /// Runs this middleware around the rest of the chain, which is invoked with
+    /// [`Next::run`]. The default implementation calls [`before`](Middleware::before) and
+    /// [`after`](Middleware::after).
+    async fn handle(
+        &self,
+        mut req: Request,
+        mut ctx: RouteContext<D>,
+        next: Next<'_, D>,
+    ) -> Result<Response> {
+        if let Some(res) = self.before(&mut req, &mut ctx).await? {
+            return Ok(res);
+        }
+        let res = next.run(req, ctx).await?;
+        self.after(res).await
+    }
+}
+
+/// The remainder of a middleware chain, ending with the route handler.
+pub struct Next<'s, D> {
+    middleware: &'s [Rc<dyn Middleware<D> + 's>],
+    endpoint: &'s Endpoint<'s, D>,
+}
+
+impl<D> core::fmt::Debug for Next<'_, D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Next")
+            .field("remaining", &self.middleware.len())
+            .finish()
+    }
+}
+
+impl<'s, D> Next<'s, D> {
+    pub(crate) fn new(
+        middleware: &'s [Rc<dyn Middleware<D> + 's>],
+        endpoint: &'s Endpoint<'s, D>,
+    ) -> Self {
+        Self {
+            middleware,
+            endpoint,
+        }
+    }
+
+    /// Run the rest of the chain with the given request and context.
+    pub async fn run(self, req: Request, ctx: RouteContext<D>) -> Result<Response> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                first
+                    .handle(
+                        req,
+                        ctx,
+                        Next {
+                            middleware: rest,
+                            endpoint: self.endpoint,
+                        },
+                    )
+                    .await
+            }
+            None => (self.endpoint)(req, ctx).await,
+        }
+    }
+}
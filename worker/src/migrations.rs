@@ -0,0 +1,183 @@
+use serde::Deserialize;
+
+use crate::{Date, Error, Result, Storage};
+
+const TABLE: &str = "_worker_migrations";
+
+/// A versioned schema migration, usually embedded at compile time with
+/// [`embed_migrations!`](crate::embed_migrations).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    /// The version of the schema after this migration. Migrations are applied in increasing
+    /// order of version.
+    pub version: u32,
+    /// A short description of the migration, recorded when it is applied.
+    pub name: &'static str,
+    /// The SQL statements of the migration, separated by semicolons.
+    pub sql: &'static str,
+    /// The name of the file the migration was embedded from, which D1 migrations are recorded
+    /// under, as wrangler does.
+    pub file_name: Option<&'static str>,
+}
+
+impl Migration {
+    pub const fn new(version: u32, name: &'static str, sql: &'static str) -> Self {
+        Self {
+            version,
+            name,
+            sql,
+            file_name: None,
+        }
+    }
+
+    pub const fn with_file_name(mut self, file_name: &'static str) -> Self {
+        self.file_name = Some(file_name);
+        self
+    }
+}
+
+/// Check that `migrations` are sorted by strictly increasing version.
+pub(crate) fn check_order(migrations: &[Migration]) -> Result<()> {
+    for pair in migrations.windows(2) {
+        if pair[0].version >= pair[1].version {
+            return Err(Error::RustError(format!(
+                "migrations must have strictly increasing versions, but {} ({}) comes before {} ({})",
+                pair[0].version, pair[0].name, pair[1].version, pair[1].name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The `migrations` above the `current` version, or all of them if none was applied.
+pub(crate) fn pending(
+    migrations: &[Migration],
+    current: Option<u32>,
+) -> impl Iterator<Item = &Migration> {
+    migrations
+        .iter()
+        .filter(move |m| current.map_or(true, |current| m.version > current))
+}
+
+impl Storage {
+    /// Bring the schema of the SQLite storage up to date by applying the `migrations` with a
+    /// version above the current one, in order. Returns the resulting schema version, or `None` if
+    /// no migration was ever applied.
+    ///
+    /// The migrations and the record of their versions in the `_worker_migrations` table are
+    /// applied in a single transaction, so a failing migration leaves the schema unchanged. Call
+    /// it from [`DurableObject::new`](crate::DurableObject::new) to migrate the object before it
+    /// handles any event:
+    ///
+    /// ```ignore
+    /// # use worker::*;
+    /// const MIGRATIONS: &[Migration] = embed_migrations!("migrations");
+    ///
+    /// #[durable_object]
+    /// pub struct Store {
+    ///     state: State,
+    /// }
+    ///
+    /// impl DurableObject for Store {
+    ///     fn new(state: State, _env: Env) -> Self {
+    ///         state.storage().migrate(MIGRATIONS).expect("failed to migrate");
+    ///         Self { state }
+    ///     }
+    ///
+    ///     async fn fetch(&self, _req: Request) -> Result<Response> {
+    ///         Response::ok("ok")
+    ///     }
+    /// }
+    /// ```
+    pub fn migrate(&self, migrations: &[Migration]) -> Result<Option<u32>> {
+        check_order(migrations)?;
+        let sql = self.sql();
+        self.transaction_sync(|| {
+            sql.exec(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {TABLE} (
+                        version INTEGER PRIMARY KEY,
+                        name TEXT NOT NULL,
+                        applied_at INTEGER NOT NULL
+                    )"
+                ),
+                None,
+            )?;
+            let current = self.schema_version()?;
+            let mut version = current;
+            for migration in pending(migrations, current) {
+                sql.exec(migration.sql, None).map_err(|e| {
+                    Error::RustError(format!(
+                        "migration {} ({}) failed: {e}",
+                        migration.version, migration.name
+                    ))
+                })?;
+                sql.exec(
+                    &format!("INSERT INTO {TABLE} (version, name, applied_at) VALUES (?, ?, ?)"),
+                    vec![
+                        i64::from(migration.version).into(),
+                        migration.name.into(),
+                        (Date::now().as_millis() as i64).into(),
+                    ],
+                )?;
+                version = Some(migration.version);
+            }
+            Ok(version)
+        })
+    }
+
+    /// The version of the last migration applied with [`migrate`](Storage::migrate), or `None`
+    /// if none was.
+    pub fn schema_version(&self) -> Result<Option<u32>> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: Option<u32>,
+        }
+
+        let sql = self.sql();
+        let exists = sql
+            .exec(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+                vec![TABLE.into()],
+            )?
+            .to_array::<serde_json::Value>()?;
+        if exists.is_empty() {
+            return Ok(None);
+        }
+        let version = sql
+            .exec(
+                &format!("SELECT MAX(version) AS version FROM {TABLE}"),
+                None,
+            )?
+            .one::<Version>()?;
+        Ok(version.version)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checks_migration_order() {
+        let a = Migration::new(1, "a", "");
+        let b = Migration::new(2, "b", "");
+        assert!(check_order(&[]).is_ok());
+        assert!(check_order(&[a, b]).is_ok());
+        assert!(check_order(&[b, a]).is_err());
+        assert!(check_order(&[a, a]).is_err());
+    }
+
+    #[test]
+    fn applies_version_zero_first() {
+        let migrations = [Migration::new(0, "init", ""), Migration::new(1, "a", "")];
+        let versions = |current| {
+            pending(&migrations, current)
+                .map(|m| m.version)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(None), [0, 1]);
+        assert_eq!(versions(Some(0)), [1]);
+        assert!(versions(Some(1)).is_empty());
+    }
+}
@@ -0,0 +1,552 @@
+use std::{
+    cell::RefCell,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures_util::{future, Stream, TryStreamExt};
+
+use crate::{Error, Result};
+
+/// Upper bound on the size of a single part's header block, so that a malicious body can't make
+/// the parser buffer an arbitrary amount of data while looking for the end of the headers.
+const MAX_HEADERS_SIZE: usize = 16 * 1024;
+
+/// A streaming parser for `multipart/form-data` bodies, created with
+/// [`Request::multipart`](crate::Request::multipart).
+///
+/// Unlike [`Request::form_data`](crate::Request::form_data), which buffers the whole body, parts
+/// are read one at a time with [`next_part`](Multipart::next_part) and their content is streamed
+/// as it arrives. A part that hasn't been fully read is skipped when the next one is requested.
+///
+/// ```no_run
+/// # use worker::*;
+/// # async fn handle(mut req: Request, bucket: Bucket) -> Result<()> {
+/// let mut multipart = req.multipart()?;
+/// while let Some(part) = multipart.next_part().await? {
+///     if part.name() == Some("file") {
+///         let key = part.file_name().unwrap_or("upload").to_string();
+///         let length = req.headers().get("X-File-Size")?.unwrap().parse().unwrap();
+///         // R2 needs to know the length of streamed uploads up front.
+///         bucket.put(key, FixedLengthStream::wrap(part, length)).execute().await?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Multipart {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl core::fmt::Debug for Multipart {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Multipart").finish()
+    }
+}
+
+impl Multipart {
+    /// Parse a `multipart/*` body delimited by `boundary` from a stream of bytes.
+    pub fn new(
+        stream: impl Stream<Item = Result<Vec<u8>>> + 'static,
+        boundary: impl AsRef<str>,
+    ) -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                parser: Parser::new(boundary.as_ref()),
+                stream: Box::pin(stream),
+                eof: false,
+                part: 0,
+            })),
+        }
+    }
+
+    /// Get the next part of the body, or `None` once all parts have been read.
+    pub async fn next_part(&mut self) -> Result<Option<MultipartPart>> {
+        loop {
+            let event = future::poll_fn(|cx| self.shared.borrow_mut().poll_event(cx)).await?;
+            match event {
+                Event::Data(_) | Event::PartEnd => continue,
+                Event::PartStart(headers) => {
+                    let mut shared = self.shared.borrow_mut();
+                    shared.part += 1;
+                    return Ok(Some(MultipartPart::new(
+                        headers,
+                        shared.part,
+                        self.shared.clone(),
+                    )));
+                }
+                Event::End => return Ok(None),
+            }
+        }
+    }
+}
+
+/// A single part of a `multipart/form-data` body: a form field or an uploaded file. The part's
+/// content is a [`Stream`] of bytes.
+pub struct MultipartPart {
+    headers: Vec<(String, String)>,
+    name: Option<String>,
+    file_name: Option<String>,
+    index: usize,
+    done: bool,
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl core::fmt::Debug for MultipartPart {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MultipartPart")
+            .field("headers", &self.headers)
+            .field("name", &self.name)
+            .field("file_name", &self.file_name)
+            .finish()
+    }
+}
+
+impl MultipartPart {
+    fn new(headers: Vec<(String, String)>, index: usize, shared: Rc<RefCell<Shared>>) -> Self {
+        let disposition = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-disposition"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        Self {
+            name: header_param(disposition, "name"),
+            file_name: header_param(disposition, "filename"),
+            headers,
+            index,
+            done: false,
+            shared,
+        }
+    }
+
+    /// The name of the form field, from the part's `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The name of the uploaded file, from the part's `Content-Disposition` header. Only set for
+    /// file parts.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The part's `Content-Type` header.
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("Content-Type")
+    }
+
+    /// Get the value of one of the part's headers. Header names are case-insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Read the rest of the part's content into memory.
+    pub async fn bytes(self) -> Result<Vec<u8>> {
+        self.try_fold(Vec::new(), |mut bytes, chunk| async move {
+            bytes.extend(chunk);
+            Ok(bytes)
+        })
+        .await
+    }
+
+    /// Read the rest of the part's content into memory as UTF-8 text.
+    pub async fn text(self) -> Result<String> {
+        String::from_utf8(self.bytes().await?).map_err(|e| Error::RustError(e.to_string()))
+    }
+}
+
+impl Stream for MultipartPart {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.borrow_mut();
+        // A later part has been requested, which skipped the rest of this one.
+        if self.done || shared.part != self.index {
+            return Poll::Ready(None);
+        }
+
+        let event = futures_util::ready!(shared.poll_event(cx));
+        drop(shared);
+        Poll::Ready(match event {
+            Ok(Event::Data(chunk)) => Some(Ok(chunk)),
+            Ok(_) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        })
+    }
+}
+
+struct Shared {
+    parser: Parser,
+    stream: Pin<Box<dyn Stream<Item = Result<Vec<u8>>>>>,
+    eof: bool,
+    /// Index of the most recently started part.
+    part: usize,
+}
+
+impl Shared {
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Result<Event>> {
+        loop {
+            if let Some(event) = self.parser.next_event()? {
+                return Poll::Ready(Ok(event));
+            }
+            if self.eof {
+                return Poll::Ready(Err(Error::RustError(
+                    "unexpected end of multipart body".into(),
+                )));
+            }
+            match futures_util::ready!(self.stream.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => self.parser.feed(&chunk),
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => self.eof = true,
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    PartStart(Vec<(String, String)>),
+    Data(Vec<u8>),
+    PartEnd,
+    End,
+}
+
+#[derive(Debug, PartialEq)]
+enum State {
+    Preamble,
+    Delimiter,
+    Headers,
+    Body,
+    Epilogue,
+}
+
+/// An incremental `multipart/*` parser. Bytes are added with [`feed`](Parser::feed) and parsed
+/// into [`Event`]s with [`next_event`](Parser::next_event), which returns `None` when it needs
+/// more input.
+struct Parser {
+    buf: Vec<u8>,
+    /// `\r\n--boundary`, the sequence that terminates a part's content.
+    delimiter: Vec<u8>,
+    state: State,
+}
+
+impl Parser {
+    fn new(boundary: &str) -> Self {
+        Self {
+            // The first delimiter may appear at the very start of the body, without a preceding
+            // line break; pretend there is one so that it's found like the others.
+            buf: b"\r\n".to_vec(),
+            delimiter: [b"\r\n--", boundary.as_bytes()].concat(),
+            state: State::Preamble,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        if self.state != State::Epilogue {
+            self.buf.extend_from_slice(chunk);
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        loop {
+            match self.state {
+                State::Preamble => match find(&self.buf, &self.delimiter) {
+                    Some(i) => {
+                        self.buf.drain(..i + self.delimiter.len());
+                        self.state = State::Delimiter;
+                    }
+                    None => {
+                        let keep = self.delimiter.len() - 1;
+                        let discard = self.buf.len().saturating_sub(keep);
+                        self.buf.drain(..discard);
+                        return Ok(None);
+                    }
+                },
+                State::Delimiter => {
+                    // Transport padding may follow the boundary before the line break.
+                    let padding = self
+                        .buf
+                        .iter()
+                        .take_while(|b| **b == b' ' || **b == b'\t')
+                        .count();
+                    let rest = &self.buf[padding..];
+                    if rest.starts_with(b"--") {
+                        self.buf.clear();
+                        self.state = State::Epilogue;
+                        return Ok(Some(Event::End));
+                    }
+                    if rest.starts_with(b"\r\n") {
+                        self.buf.drain(..padding + 2);
+                        self.state = State::Headers;
+                        continue;
+                    }
+                    if rest.len() < 2 {
+                        return Ok(None);
+                    }
+                    return Err(Error::RustError(
+                        "invalid multipart boundary delimiter".into(),
+                    ));
+                }
+                State::Headers => {
+                    let end = if self.buf.starts_with(b"\r\n") {
+                        Some(0)
+                    } else {
+                        find(&self.buf, b"\r\n\r\n").map(|i| i + 2)
+                    };
+                    let Some(end) = end else {
+                        if self.buf.len() > MAX_HEADERS_SIZE {
+                            return Err(Error::RustError(
+                                "multipart part headers are too large".into(),
+                            ));
+                        }
+                        return Ok(None);
+                    };
+                    let headers = parse_headers(&self.buf[..end]);
+                    self.buf.drain(..end + 2);
+                    self.state = State::Body;
+                    return Ok(Some(Event::PartStart(headers)));
+                }
+                State::Body => match find(&self.buf, &self.delimiter) {
+                    Some(0) => {
+                        self.buf.drain(..self.delimiter.len());
+                        self.state = State::Delimiter;
+                        return Ok(Some(Event::PartEnd));
+                    }
+                    Some(i) => return Ok(Some(Event::Data(self.buf.drain(..i).collect()))),
+                    None => {
+                        // The end of the buffer may hold the start of a delimiter.
+                        let keep = self.delimiter.len() - 1;
+                        if self.buf.len() <= keep {
+                            return Ok(None);
+                        }
+                        let len = self.buf.len() - keep;
+                        return Ok(Some(Event::Data(self.buf.drain(..len).collect())));
+                    }
+                },
+                State::Epilogue => return Ok(Some(Event::End)),
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Parse a header block, where each header is terminated by a line break.
+fn parse_headers(block: &[u8]) -> Vec<(String, String)> {
+    String::from_utf8_lossy(block)
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Get a parameter of a header value such as `form-data; name="file"; filename="a.txt"`.
+fn header_param(value: &str, param: &str) -> Option<String> {
+    let mut rest = value.split_once(';')?.1;
+    loop {
+        let (key, after_key) = rest.split_once('=')?;
+        let after_key = after_key.trim_start();
+        let (val, after_val) = match after_key.strip_prefix('"') {
+            Some(quoted) => {
+                let mut val = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => val.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            end = i + 1;
+                            break;
+                        }
+                        c => val.push(c),
+                    }
+                }
+                (val, &quoted[end..])
+            }
+            None => {
+                let end = after_key.find(';').unwrap_or(after_key.len());
+                (after_key[..end].trim().to_string(), &after_key[end..])
+            }
+        };
+        if key.trim().eq_ignore_ascii_case(param) {
+            return Some(val);
+        }
+        rest = after_val.split_once(';')?.1;
+    }
+}
+
+/// Get the boundary parameter of a `multipart/*` content type.
+pub(crate) fn boundary(content_type: &str) -> Option<String> {
+    let (mime, _) = content_type.split_once(';')?;
+    if !mime.trim().to_ascii_lowercase().starts_with("multipart/") {
+        return None;
+    }
+    header_param(content_type, "boundary").filter(|boundary| !boundary.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::{stream, FutureExt};
+
+    use super::*;
+
+    const BODY: &[u8] = b"preamble\r\n\
+--XyZ\r\n\
+Content-Disposition: form-data; name=\"field\"\r\n\
+\r\n\
+value\r\n\
+--XyZ \r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"a \\\"b\\\".txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+line one\r\n--XyNot a delimiter\r\nline two\r\n\
+--XyZ--\r\n\
+epilogue";
+
+    fn parse(chunk_size: usize) -> Vec<Event> {
+        let mut parser = Parser::new("XyZ");
+        let mut events = Vec::new();
+        for chunk in BODY.chunks(chunk_size) {
+            parser.feed(chunk);
+            while let Some(event) = parser.next_event().unwrap() {
+                let end = event == Event::End;
+                // Merge consecutive data events, whose boundaries depend on the chunk size.
+                match (events.last_mut(), event) {
+                    (Some(Event::Data(prev)), Event::Data(data)) => prev.extend(data),
+                    (_, event) => events.push(event),
+                }
+                if end {
+                    return events;
+                }
+            }
+        }
+        panic!("no end event: {events:?}");
+    }
+
+    #[test]
+    fn parser_emits_parts_for_any_chunk_size() {
+        let expected = vec![
+            Event::PartStart(vec![(
+                "Content-Disposition".into(),
+                "form-data; name=\"field\"".into(),
+            )]),
+            Event::Data(b"value".to_vec()),
+            Event::PartEnd,
+            Event::PartStart(vec![
+                (
+                    "Content-Disposition".into(),
+                    "form-data; name=\"file\"; filename=\"a \\\"b\\\".txt\"".into(),
+                ),
+                ("Content-Type".into(), "text/plain".into()),
+            ]),
+            Event::Data(b"line one\r\n--XyNot a delimiter\r\nline two".to_vec()),
+            Event::PartEnd,
+            Event::End,
+        ];
+        for chunk_size in [1, 2, 3, 7, 16, BODY.len()] {
+            assert_eq!(parse(chunk_size), expected, "chunk size {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn parser_rejects_oversized_headers() {
+        let mut parser = Parser::new("b");
+        parser.feed(b"--b\r\nX-Big: ");
+        parser.feed(&vec![b'a'; MAX_HEADERS_SIZE]);
+        assert!(parser.next_event().is_err());
+    }
+
+    #[test]
+    fn header_params() {
+        let value = "form-data; name=\"file\"; filename=\"a;b.txt\"";
+        assert_eq!(header_param(value, "name").as_deref(), Some("file"));
+        assert_eq!(header_param(value, "filename").as_deref(), Some("a;b.txt"));
+        assert_eq!(header_param(value, "missing"), None);
+        assert_eq!(
+            header_param("form-data; name=plain", "name").as_deref(),
+            Some("plain")
+        );
+    }
+
+    #[test]
+    fn boundary_from_content_type() {
+        assert_eq!(
+            boundary("multipart/form-data; boundary=----abc").as_deref(),
+            Some("----abc")
+        );
+        assert_eq!(
+            boundary("Multipart/Form-Data; charset=utf-8; boundary=\"a b\"").as_deref(),
+            Some("a b")
+        );
+        assert_eq!(boundary("application/x-www-form-urlencoded"), None);
+        assert_eq!(boundary("multipart/form-data"), None);
+    }
+
+    #[test]
+    fn multipart_streams_parts() {
+        let chunks = BODY
+            .chunks(5)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect::<Vec<_>>();
+        let mut multipart = Multipart::new(stream::iter(chunks), "XyZ");
+
+        let field = multipart
+            .next_part()
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(field.name(), Some("field"));
+        assert_eq!(field.file_name(), None);
+        // Skipped without being read.
+        drop(field);
+
+        let file = multipart
+            .next_part()
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.name(), Some("file"));
+        assert_eq!(file.file_name(), Some("a \"b\".txt"));
+        assert_eq!(file.content_type(), Some("text/plain"));
+        assert_eq!(
+            file.text().now_or_never().unwrap().unwrap(),
+            "line one\r\n--XyNot a delimiter\r\nline two"
+        );
+
+        assert!(multipart
+            .next_part()
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn multipart_errors_on_truncated_body() {
+        let body = b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nabc".to_vec();
+        let mut multipart = Multipart::new(stream::iter([Ok(body)]), "XyZ");
+        let part = multipart
+            .next_part()
+            .now_or_never()
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(part.bytes().now_or_never().unwrap().is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use serde_json::{json, Map, Value};
+
+use crate::router::RouteInfo;
+
+/// Documentation attached to a route with [`Router::describe`](crate::Router::describe), used to
+/// build the operation in the router's OpenAPI document. Schemas are JSON Schema objects as
+/// accepted by OpenAPI 3.0.
+///
+/// **Requires** `openapi` feature.
+#[derive(Debug, Clone, Default)]
+pub struct RouteDoc {
+    summary: Option<String>,
+    description: Option<String>,
+    operation_id: Option<String>,
+    tags: Vec<String>,
+    params: Vec<(String, Value)>,
+    request_body: Option<Value>,
+    responses: Vec<(u16, String, Option<Value>)>,
+}
+
+impl RouteDoc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a short summary of what the operation does.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Set a verbose explanation of the operation.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set a unique identifier for the operation.
+    pub fn operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+
+    /// Add a tag used to group operations.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Set the schema of a path parameter. Parameters without a schema are documented as strings.
+    pub fn param(mut self, name: impl Into<String>, schema: Value) -> Self {
+        self.params.push((name.into(), schema));
+        self
+    }
+
+    /// Set the schema of the operation's `application/json` request body.
+    pub fn json_request(mut self, schema: Value) -> Self {
+        self.request_body = Some(schema);
+        self
+    }
+
+    /// Document a response without a body.
+    pub fn response(mut self, status: u16, description: impl Into<String>) -> Self {
+        self.responses.push((status, description.into(), None));
+        self
+    }
+
+    /// Document a response with an `application/json` body.
+    pub fn json_response(
+        mut self,
+        status: u16,
+        description: impl Into<String>,
+        schema: Value,
+    ) -> Self {
+        self.responses
+            .push((status, description.into(), Some(schema)));
+        self
+    }
+}
+
+/// Build an OpenAPI 3.0 document describing `routes`.
+pub(crate) fn document<'r>(
+    routes: impl IntoIterator<Item = &'r RouteInfo>,
+    title: &str,
+    version: &str,
+) -> Value {
+    let mut paths = Map::new();
+    for route in routes {
+        let item = paths
+            .entry(openapi_path(route.pattern()))
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[route.method().as_ref().to_ascii_lowercase()] = operation(route);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": title,
+            "version": version,
+        },
+        "paths": paths,
+    })
+}
+
+fn operation(route: &RouteInfo) -> Value {
+    let default_doc = RouteDoc::default();
+    let doc = route.doc().unwrap_or(&default_doc);
+
+    let mut operation = Map::new();
+    if let Some(summary) = &doc.summary {
+        operation.insert("summary".into(), summary.as_str().into());
+    }
+    if let Some(description) = &doc.description {
+        operation.insert("description".into(), description.as_str().into());
+    }
+    if let Some(operation_id) = &doc.operation_id {
+        operation.insert("operationId".into(), operation_id.as_str().into());
+    }
+    if !doc.tags.is_empty() {
+        operation.insert("tags".into(), json!(doc.tags));
+    }
+
+    let parameters = route
+        .params()
+        .iter()
+        .map(|name| {
+            let schema = doc
+                .params
+                .iter()
+                .find(|(param, _)| param == name)
+                .map(|(_, schema)| schema.clone())
+                .unwrap_or_else(|| json!({ "type": "string" }));
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": schema,
+            })
+        })
+        .collect::<Vec<_>>();
+    if !parameters.is_empty() {
+        operation.insert("parameters".into(), parameters.into());
+    }
+
+    if let Some(schema) = &doc.request_body {
+        operation.insert(
+            "requestBody".into(),
+            json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema } },
+            }),
+        );
+    }
+
+    let mut responses = Map::new();
+    for (status, description, schema) in &doc.responses {
+        let mut response = json!({ "description": description });
+        if let Some(schema) = schema {
+            response["content"] = json!({ "application/json": { "schema": schema } });
+        }
+        responses.insert(status.to_string(), response);
+    }
+    if responses.is_empty() {
+        responses.insert("default".into(), json!({ "description": "" }));
+    }
+    operation.insert("responses".into(), responses.into());
+
+    operation.into()
+}
+
+/// Convert a router pattern such as `/user/:id/*path` into an OpenAPI path template such as
+/// `/user/{id}/{path}`.
+fn openapi_path(pattern: &str) -> String {
+    pattern
+        .split('/')
+        .map(|segment| match segment.find([':', '*']) {
+            Some(i) => format!("{}{{{}}}", &segment[..i], &segment[i + 1..]),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Method, Request, Response, Result, RouteContext, Router};
+
+    fn handler(_: Request, _: RouteContext<()>) -> Result<Response> {
+        unreachable!()
+    }
+
+    #[test]
+    fn openapi_path_converts_placeholders() {
+        assert_eq!(openapi_path("/"), "/");
+        assert_eq!(openapi_path("/user/:id"), "/user/{id}");
+        assert_eq!(
+            openapi_path("/account/:id/files/*path"),
+            "/account/{id}/files/{path}"
+        );
+    }
+
+    #[test]
+    fn document_lists_operations_and_params() {
+        let router = Router::new()
+            .get("/user/:id", handler)
+            .post("/user/:id", handler)
+            .describe(
+                Method::Post,
+                "/user/:id",
+                RouteDoc::new()
+                    .summary("Update a user")
+                    .param("id", json!({ "type": "integer" }))
+                    .json_request(json!({ "type": "object" }))
+                    .json_response(200, "The updated user", json!({ "type": "object" })),
+            );
+
+        let doc = router.openapi("Users", "1.0.0");
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert_eq!(doc["info"]["title"], "Users");
+
+        let get = &doc["paths"]["/user/{id}"]["get"];
+        assert_eq!(get["parameters"][0]["name"], "id");
+        assert_eq!(get["parameters"][0]["schema"]["type"], "string");
+        assert!(get["responses"]["default"].is_object());
+
+        let post = &doc["paths"]["/user/{id}"]["post"];
+        assert_eq!(post["summary"], "Update a user");
+        assert_eq!(post["parameters"][0]["schema"]["type"], "integer");
+        assert_eq!(
+            post["requestBody"]["content"]["application/json"]["schema"]["type"],
+            "object"
+        );
+        assert_eq!(post["responses"]["200"]["description"], "The updated user");
+    }
+}
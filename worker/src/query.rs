@@ -0,0 +1,670 @@
+//! A small SQL query builder, with bound parameters, for the SQLite dialect shared by D1 and the
+//! SQLite storage of Durable Objects.
+//!
+//! Queries are run through a [`SqlExecutor`], so code written against it works with either:
+//!
+//! ```no_run
+//! # use worker::{query::*, *};
+//! #[derive(FromRow)]
+//! struct User {
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! async fn rename(db: &impl SqlExecutor, id: i64, name: &str) -> Result<Option<User>> {
+//!     Update::table("users")
+//!         .set("name", name)
+//!         .where_eq("id", id)
+//!         .build()?
+//!         .execute(db)
+//!         .await?;
+//!     Select::from("users")
+//!         .columns(["id", "name"])
+//!         .where_eq("id", id)
+//!         .build()
+//!         .fetch_optional(db)
+//!         .await
+//! }
+//! ```
+
+use async_trait::async_trait;
+
+#[cfg(feature = "d1")]
+use crate::{D1Database, D1DatabaseSession, D1PreparedStatement};
+use crate::{Error, FromRow, Result, SqlRow, SqlStorage, SqlStorageValue, ToSqlValue};
+
+/// Runs SQL statements with positional `?` parameters against D1 or the SQLite storage of a
+/// Durable Object.
+#[async_trait(?Send)]
+pub trait SqlExecutor {
+    /// Run a statement, returning the number of rows it inserted, updated or deleted.
+    async fn execute(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<usize>;
+
+    /// Run a statement, returning the rows it produced.
+    async fn query(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<Vec<SqlRow>>;
+
+    /// Run a statement, converting the rows it produced.
+    async fn query_as<T: FromRow>(
+        &self,
+        sql: &str,
+        params: Vec<SqlStorageValue>,
+    ) -> Result<Vec<T>> {
+        self.query(sql, params)
+            .await?
+            .into_iter()
+            .map(T::from_row)
+            .collect()
+    }
+}
+
+#[async_trait(?Send)]
+impl SqlExecutor for SqlStorage {
+    async fn execute(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<usize> {
+        // Run the statement to completion before counting its changes.
+        self.exec(sql, params)?
+            .raw()
+            .try_for_each(|row| row.map(drop))?;
+        let changes = self
+            .exec("SELECT changes()", None)?
+            .raw()
+            .next()
+            .transpose()?
+            .and_then(|row| row.into_iter().next());
+        match changes {
+            Some(SqlStorageValue::Integer(changes)) => Ok(changes.max(0) as usize),
+            _ => Ok(0),
+        }
+    }
+
+    async fn query(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<Vec<SqlRow>> {
+        self.exec(sql, params)?.rows()
+    }
+}
+
+#[cfg(feature = "d1")]
+#[async_trait(?Send)]
+impl SqlExecutor for D1Database {
+    async fn execute(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<usize> {
+        d1_execute(self.prepare(sql), params).await
+    }
+
+    async fn query(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<Vec<SqlRow>> {
+        d1_bind(self.prepare(sql), params)?.rows().await
+    }
+}
+
+#[cfg(feature = "d1")]
+#[async_trait(?Send)]
+impl SqlExecutor for D1DatabaseSession {
+    async fn execute(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<usize> {
+        d1_execute(self.prepare(sql), params).await
+    }
+
+    async fn query(&self, sql: &str, params: Vec<SqlStorageValue>) -> Result<Vec<SqlRow>> {
+        d1_bind(self.prepare(sql), params)?.rows().await
+    }
+}
+
+#[cfg(feature = "d1")]
+fn d1_bind(
+    statement: D1PreparedStatement,
+    params: Vec<SqlStorageValue>,
+) -> Result<D1PreparedStatement> {
+    let values: Vec<_> = params.into_iter().map(Into::into).collect();
+    statement.bind(&values)
+}
+
+#[cfg(feature = "d1")]
+async fn d1_execute(statement: D1PreparedStatement, params: Vec<SqlStorageValue>) -> Result<usize> {
+    let result = d1_bind(statement, params)?.run().await?;
+    Ok(result.meta()?.and_then(|meta| meta.changes).unwrap_or(0))
+}
+
+/// A statement with its bound parameters, ready to run through a [`SqlExecutor`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    sql: String,
+    params: Vec<SqlStorageValue>,
+}
+
+impl Query {
+    /// A statement with positional `?` parameters, bound with [`bind`](Query::bind).
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Bind the next parameter.
+    pub fn bind(mut self, value: impl ToSqlValue) -> Self {
+        self.params.push(value.to_sql_value());
+        self
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    pub fn params(&self) -> &[SqlStorageValue] {
+        &self.params
+    }
+
+    /// Run the statement, returning the number of rows it changed.
+    pub async fn execute(&self, executor: &impl SqlExecutor) -> Result<usize> {
+        executor.execute(&self.sql, self.params.clone()).await
+    }
+
+    /// Run the statement, converting all the rows it produced.
+    pub async fn fetch_all<T: FromRow>(&self, executor: &impl SqlExecutor) -> Result<Vec<T>> {
+        executor.query_as(&self.sql, self.params.clone()).await
+    }
+
+    /// Run the statement, converting the first row it produced, if any.
+    pub async fn fetch_optional<T: FromRow>(
+        &self,
+        executor: &impl SqlExecutor,
+    ) -> Result<Option<T>> {
+        let row = executor
+            .query(&self.sql, self.params.clone())
+            .await?
+            .into_iter()
+            .next();
+        row.map(T::from_row).transpose()
+    }
+
+    /// Run the statement, converting the first row it produced. Fails if there is none.
+    pub async fn fetch_one<T: FromRow>(&self, executor: &impl SqlExecutor) -> Result<T> {
+        self.fetch_optional(executor)
+            .await?
+            .ok_or_else(|| Error::RustError("query returned no rows".into()))
+    }
+}
+
+/// Quote an identifier, such as a table or column name.
+pub(crate) fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn quote_list<'a>(identifiers: impl IntoIterator<Item = &'a String>) -> String {
+    identifiers
+        .into_iter()
+        .map(|identifier| quote(identifier))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The `WHERE` clause of a statement, as conditions joined with `AND`.
+#[derive(Debug, Clone, Default)]
+struct Conditions {
+    sql: Vec<String>,
+    params: Vec<SqlStorageValue>,
+}
+
+impl Conditions {
+    fn push(&mut self, sql: String, params: impl IntoIterator<Item = SqlStorageValue>) {
+        self.sql.push(sql);
+        self.params.extend(params);
+    }
+
+    fn write(self, query: &mut Query) {
+        if !self.sql.is_empty() {
+            query.sql.push_str(" WHERE ");
+            query.sql.push_str(&self.sql.join(" AND "));
+            query.params.extend(self.params);
+        }
+    }
+}
+
+macro_rules! impl_conditions {
+    ($ty:ty) => {
+        impl $ty {
+            /// Only match rows where `column` equals `value`.
+            pub fn where_eq(mut self, column: &str, value: impl ToSqlValue) -> Self {
+                self.conditions
+                    .push(format!("{} = ?", quote(column)), [value.to_sql_value()]);
+                self
+            }
+
+            /// Only match rows where `column` is `NULL`.
+            pub fn where_null(mut self, column: &str) -> Self {
+                self.conditions
+                    .push(format!("{} IS NULL", quote(column)), []);
+                self
+            }
+
+            /// Only match rows where `column` equals one of `values`. Matches no rows if
+            /// `values` is empty.
+            pub fn where_in<V: ToSqlValue>(
+                mut self,
+                column: &str,
+                values: impl IntoIterator<Item = V>,
+            ) -> Self {
+                let values: Vec<_> = values.into_iter().map(|v| v.to_sql_value()).collect();
+                let placeholders = vec!["?"; values.len()].join(", ");
+                self.conditions
+                    .push(format!("{} IN ({placeholders})", quote(column)), values);
+                self
+            }
+
+            /// Only match rows satisfying the SQL expression `condition`, with its `?`
+            /// parameters bound to `params`.
+            pub fn filter<V: ToSqlValue>(
+                mut self,
+                condition: &str,
+                params: impl IntoIterator<Item = V>,
+            ) -> Self {
+                self.conditions.push(
+                    format!("({condition})"),
+                    params.into_iter().map(|v| v.to_sql_value()),
+                );
+                self
+            }
+        }
+    };
+}
+
+/// The direction of an `ORDER BY` clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// Builds a `SELECT` statement.
+#[derive(Debug, Clone)]
+pub struct Select {
+    table: String,
+    columns: Vec<String>,
+    conditions: Conditions,
+    order_by: Vec<(String, Order)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl Select {
+    /// Select all the columns of `table`.
+    pub fn from(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            columns: Vec::new(),
+            conditions: Conditions::default(),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Select only `columns`.
+    pub fn columns<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns.extend(columns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sort the rows by `column`, after any previous sort columns.
+    pub fn order_by(mut self, column: &str, order: Order) -> Self {
+        self.order_by.push((column.to_string(), order));
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn build(self) -> Query {
+        let columns = match self.columns.is_empty() {
+            true => "*".to_string(),
+            false => quote_list(&self.columns),
+        };
+        let mut query = Query::new(format!("SELECT {columns} FROM {}", quote(&self.table)));
+        self.conditions.write(&mut query);
+        if !self.order_by.is_empty() {
+            let order_by = self
+                .order_by
+                .iter()
+                .map(|(column, order)| {
+                    let order = match order {
+                        Order::Asc => "ASC",
+                        Order::Desc => "DESC",
+                    };
+                    format!("{} {order}", quote(column))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            query.sql.push_str(&format!(" ORDER BY {order_by}"));
+        }
+        // SQLite only accepts OFFSET after a LIMIT, where -1 means no limit.
+        if self.limit.is_some() || self.offset.is_some() {
+            query.sql.push_str(" LIMIT ?");
+            query.params.push(integer(self.limit.map_or(-1, clamp)));
+        }
+        if let Some(offset) = self.offset {
+            query.sql.push_str(" OFFSET ?");
+            query.params.push(integer(clamp(offset)));
+        }
+        query
+    }
+}
+
+fn clamp(value: u64) -> i64 {
+    value.min(i64::MAX as u64) as i64
+}
+
+fn integer(value: i64) -> SqlStorageValue {
+    SqlStorageValue::Integer(value)
+}
+
+impl_conditions!(Select);
+
+/// What an [`Insert`] does when it conflicts with an existing row.
+#[derive(Debug, Clone)]
+enum OnConflict {
+    Nothing(Vec<String>),
+    Update(Vec<String>),
+}
+
+/// Builds an `INSERT` statement, optionally turned into an upsert with
+/// [`on_conflict_update`](Insert::on_conflict_update).
+#[derive(Debug, Clone)]
+pub struct Insert {
+    table: String,
+    columns: Vec<String>,
+    params: Vec<SqlStorageValue>,
+    on_conflict: Option<OnConflict>,
+}
+
+impl Insert {
+    pub fn into(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            columns: Vec::new(),
+            params: Vec::new(),
+            on_conflict: None,
+        }
+    }
+
+    /// Set `column` of the new row to `value`.
+    pub fn value(mut self, column: &str, value: impl ToSqlValue) -> Self {
+        self.columns.push(column.to_string());
+        self.params.push(value.to_sql_value());
+        self
+    }
+
+    /// Skip the insert when it conflicts with an existing row on the unique `target` columns.
+    pub fn on_conflict_do_nothing<S: Into<String>>(
+        mut self,
+        target: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.on_conflict = Some(OnConflict::Nothing(
+            target.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    /// When the insert conflicts with an existing row on the unique `target` columns, update the
+    /// other inserted columns of that row instead. If all the inserted columns are in `target`,
+    /// the insert is skipped, as with [`on_conflict_do_nothing`](Insert::on_conflict_do_nothing).
+    pub fn on_conflict_update<S: Into<String>>(
+        mut self,
+        target: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.on_conflict = Some(OnConflict::Update(
+            target.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    /// Build the statement. Without any [`value`](Insert::value), the row is inserted with the
+    /// default values of all its columns. Fails for an upsert without any value, which SQLite
+    /// does not support.
+    pub fn build(self) -> Result<Query> {
+        if self.columns.is_empty() {
+            if self.on_conflict.is_some() {
+                return Err(Error::RustError(format!(
+                    "upsert into {} inserts no values",
+                    self.table
+                )));
+            }
+            return Ok(Query::new(format!(
+                "INSERT INTO {} DEFAULT VALUES",
+                quote(&self.table)
+            )));
+        }
+
+        let placeholders = vec!["?"; self.columns.len()].join(", ");
+        let mut query = Query::new(format!(
+            "INSERT INTO {} ({}) VALUES ({placeholders})",
+            quote(&self.table),
+            quote_list(&self.columns)
+        ));
+        match self.on_conflict {
+            Some(OnConflict::Nothing(target)) => {
+                query.sql.push_str(&format!(
+                    " ON CONFLICT ({}) DO NOTHING",
+                    quote_list(&target)
+                ));
+            }
+            Some(OnConflict::Update(target)) => {
+                let update: Vec<_> = self
+                    .columns
+                    .iter()
+                    .filter(|column| !target.contains(column))
+                    .collect();
+                if update.is_empty() {
+                    query.sql.push_str(&format!(
+                        " ON CONFLICT ({}) DO NOTHING",
+                        quote_list(&target)
+                    ));
+                } else {
+                    let set = update
+                        .iter()
+                        .map(|column| format!("{0} = excluded.{0}", quote(column)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    query.sql.push_str(&format!(
+                        " ON CONFLICT ({}) DO UPDATE SET {set}",
+                        quote_list(&target)
+                    ));
+                }
+            }
+            None => {}
+        }
+        query.params = self.params;
+        Ok(query)
+    }
+}
+
+/// Builds an `UPDATE` statement.
+#[derive(Debug, Clone)]
+pub struct Update {
+    table: String,
+    columns: Vec<String>,
+    params: Vec<SqlStorageValue>,
+    conditions: Conditions,
+}
+
+impl Update {
+    pub fn table(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            columns: Vec::new(),
+            params: Vec::new(),
+            conditions: Conditions::default(),
+        }
+    }
+
+    /// Set `column` to `value`.
+    pub fn set(mut self, column: &str, value: impl ToSqlValue) -> Self {
+        self.columns.push(column.to_string());
+        self.params.push(value.to_sql_value());
+        self
+    }
+
+    /// Build the statement. Fails if no column was [`set`](Update::set).
+    pub fn build(self) -> Result<Query> {
+        if self.columns.is_empty() {
+            return Err(Error::RustError(format!(
+                "update of {} sets no columns",
+                self.table
+            )));
+        }
+        let set = self
+            .columns
+            .iter()
+            .map(|column| format!("{} = ?", quote(column)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut query = Query::new(format!("UPDATE {} SET {set}", quote(&self.table)));
+        query.params = self.params;
+        self.conditions.write(&mut query);
+        Ok(query)
+    }
+}
+
+impl_conditions!(Update);
+
+/// Builds a `DELETE` statement.
+#[derive(Debug, Clone)]
+pub struct Delete {
+    table: String,
+    conditions: Conditions,
+}
+
+impl Delete {
+    pub fn from(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            conditions: Conditions::default(),
+        }
+    }
+
+    pub fn build(self) -> Query {
+        let mut query = Query::new(format!("DELETE FROM {}", quote(&self.table)));
+        self.conditions.write(&mut query);
+        query
+    }
+}
+
+impl_conditions!(Delete);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_selects() {
+        let query = Select::from("users")
+            .columns(["id", "name"])
+            .where_eq("org", 3)
+            .where_in("role", ["admin", "owner"])
+            .filter("age > ?", [18])
+            .order_by("name", Order::Asc)
+            .limit(10)
+            .offset(20)
+            .build();
+        assert_eq!(
+            query.sql(),
+            "SELECT \"id\", \"name\" FROM \"users\" WHERE \"org\" = ? AND \"role\" IN (?, ?) \
+             AND (age > ?) ORDER BY \"name\" ASC LIMIT ? OFFSET ?"
+        );
+        assert_eq!(
+            query.params(),
+            &[
+                SqlStorageValue::Integer(3),
+                SqlStorageValue::String("admin".into()),
+                SqlStorageValue::String("owner".into()),
+                SqlStorageValue::Integer(18),
+                SqlStorageValue::Integer(10),
+                SqlStorageValue::Integer(20),
+            ]
+        );
+
+        let query = Select::from("we\"ird").offset(5).build();
+        assert_eq!(query.params()[0], SqlStorageValue::Integer(-1));
+        assert_eq!(query.sql(), "SELECT * FROM \"we\"\"ird\" LIMIT ? OFFSET ?");
+    }
+
+    #[test]
+    fn builds_upserts() {
+        let query = Insert::into("users")
+            .value("id", 1)
+            .value("name", "Ada")
+            .value("email", None::<String>)
+            .on_conflict_update(["id"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "INSERT INTO \"users\" (\"id\", \"name\", \"email\") VALUES (?, ?, ?) \
+             ON CONFLICT (\"id\") DO UPDATE SET \"name\" = excluded.\"name\", \
+             \"email\" = excluded.\"email\""
+        );
+        assert_eq!(query.params()[2], SqlStorageValue::Null);
+
+        // The update columns don't depend on the order of the calls.
+        let query = Insert::into("t")
+            .on_conflict_update(["id"])
+            .value("id", 1)
+            .value("name", "x")
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "INSERT INTO \"t\" (\"id\", \"name\") VALUES (?, ?) \
+             ON CONFLICT (\"id\") DO UPDATE SET \"name\" = excluded.\"name\""
+        );
+
+        let query = Insert::into("t")
+            .value("id", 1)
+            .on_conflict_update(["id"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "INSERT INTO \"t\" (\"id\") VALUES (?) ON CONFLICT (\"id\") DO NOTHING"
+        );
+    }
+
+    #[test]
+    fn builds_inserts_of_default_values() {
+        let query = Insert::into("t").build().unwrap();
+        assert_eq!(query.sql(), "INSERT INTO \"t\" DEFAULT VALUES");
+        assert!(query.params().is_empty());
+
+        assert!(Insert::into("t")
+            .on_conflict_update(["id"])
+            .build()
+            .is_err());
+        assert!(Insert::into("t")
+            .on_conflict_do_nothing(["id"])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn builds_updates_and_deletes() {
+        let query = Update::table("users")
+            .set("name", "Bob")
+            .where_eq("id", 1)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.sql(),
+            "UPDATE \"users\" SET \"name\" = ? WHERE \"id\" = ?"
+        );
+        assert_eq!(query.params().len(), 2);
+        assert!(Update::table("users").where_eq("id", 1).build().is_err());
+
+        let query = Delete::from("users").where_null("deleted_at").build();
+        assert_eq!(
+            query.sql(),
+            "DELETE FROM \"users\" WHERE \"deleted_at\" IS NULL"
+        );
+    }
+}
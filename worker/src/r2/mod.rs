@@ -1,6 +1,7 @@
 use std::{collections::HashMap, convert::TryInto, ops::Deref};
 
 pub use builder::*;
+pub use serve::*;
 
 use js_sys::futures::JsFuture;
 use js_sys::{JsString, Reflect, Uint8Array};
@@ -17,6 +18,7 @@ use crate::{
 };
 
 mod builder;
+mod serve;
 
 /// An instance of the R2 bucket binding.
 #[derive(Debug, Clone)]
@@ -51,6 +53,17 @@ impl Bucket {
         }
     }
 
+    /// Builds the HTTP [Response](crate::Response) for a `GET` or `HEAD` request of the object
+    /// stored under `key`, honoring the request's `Range` and conditional headers. Refer to
+    /// [ServeOptionsBuilder::execute].
+    pub fn serve(&self, key: impl Into<String>) -> ServeOptionsBuilder<'_> {
+        ServeOptionsBuilder {
+            bucket: self,
+            key: key.into(),
+            cache_control: None,
+        }
+    }
+
     /// Stores the given `value` and metadata under the associated `key`. Once the write succeeds,
     /// returns an [Object] containing metadata about the stored Object.
     ///
@@ -0,0 +1,326 @@
+use crate::{
+    date::{http_date, parse_http_date},
+    headers::{ETag, IfMatch, IfNoneMatch, Range as RangeHeader, TypedHeader},
+    Headers, Method, Request, Response, ResponseBody, Result,
+};
+
+use super::{Bucket, Conditional, Object, Range};
+
+/// How many times a ranged `GET` looks the object up again when it is replaced between the
+/// [head](crate::r2::Bucket::head) and the read of the range.
+const RANGE_ATTEMPTS: usize = 3;
+
+/// Options for configuring the [serve](crate::r2::Bucket::serve) operation.
+#[derive(Debug)]
+pub struct ServeOptionsBuilder<'bucket> {
+    pub(crate) bucket: &'bucket Bucket,
+    pub(crate) key: String,
+    pub(crate) cache_control: Option<String>,
+}
+
+impl ServeOptionsBuilder<'_> {
+    /// The `Cache-Control` header sent for objects that weren't uploaded with one in their
+    /// [HttpMetadata](super::HttpMetadata).
+    pub fn cache_control(mut self, cache_control: impl Into<String>) -> Self {
+        self.cache_control = Some(cache_control.into());
+        self
+    }
+
+    /// Answers `req` with the object.
+    ///
+    /// * `404 Not Found` if the key doesn't exist.
+    /// * `304 Not Modified` or `412 Precondition Failed` if the `If-None-Match`,
+    ///   `If-Modified-Since`, `If-Match` or `If-Unmodified-Since` headers aren't satisfied.
+    /// * `206 Partial Content` with a `Content-Range` header for a single byte range, or
+    ///   `416 Range Not Satisfiable` if the range lies outside the object or asks for multiple
+    ///   ranges. A `Range` accompanied by `If-Range` is ignored and the whole object is sent.
+    /// * `200 OK` otherwise, with no body for `HEAD` requests.
+    /// * `405 Method Not Allowed` for methods other than `GET` and `HEAD`.
+    ///
+    /// Successful responses carry the object's HTTP metadata along with `ETag`, `Last-Modified`
+    /// and `Accept-Ranges`. Ranged requests look up the object's size with a
+    /// [head](crate::r2::Bucket::head) before reading it, only if its `ETag` is unchanged. If the
+    /// object keeps being replaced in between, the response is a `412 Precondition Failed`.
+    pub async fn execute(self, req: &Request) -> Result<Response> {
+        let method = req.method();
+        if !matches!(method, Method::Get | Method::Head) {
+            let headers = Headers::new();
+            headers.set("Allow", "GET, HEAD")?;
+            return Ok(Response::builder()
+                .with_status(405)
+                .with_headers(headers)
+                .empty());
+        }
+
+        let preconditions = Preconditions::from_headers(req.headers())?;
+        let range = match method {
+            // Malformed ranges, and ranges in units other than bytes, are ignored.
+            Method::Get if !req.headers().has("If-Range")? => {
+                req.headers().header::<RangeHeader>().ok().flatten()
+            }
+            _ => None,
+        };
+
+        for _ in 0..RANGE_ATTEMPTS {
+            if let Some(response) = self
+                .respond(&method, &preconditions, range.as_ref())
+                .await?
+            {
+                return Ok(response);
+            }
+        }
+        Ok(Response::builder().with_status(412).empty())
+    }
+
+    /// The response for the current version of the object, or `None` if it was replaced while
+    /// reading a range.
+    async fn respond(
+        &self,
+        method: &Method,
+        preconditions: &Preconditions,
+        range: Option<&RangeHeader>,
+    ) -> Result<Option<Response>> {
+        let object = match (method, range) {
+            (Method::Head, _) | (_, Some(_)) => self.bucket.head(&self.key).await?,
+            _ => {
+                self.bucket
+                    .get(&self.key)
+                    .only_if(preconditions.conditional())
+                    .execute()
+                    .await?
+            }
+        };
+        let Some(object) = object else {
+            return Response::error("Not Found", 404).map(Some);
+        };
+
+        let etag = ETag::parse(&object.http_etag())?;
+        let last_modified = object.uploaded().as_millis() / 1000;
+        let headers = self.headers(&object, &etag, last_modified)?;
+
+        if let Some(status) = preconditions.evaluate(&etag, last_modified) {
+            if status == 412 {
+                return Ok(Some(Response::builder().with_status(412).empty()));
+            }
+            return Ok(Some(
+                Response::builder()
+                    .with_status(304)
+                    .with_headers(headers)
+                    .empty(),
+            ));
+        }
+
+        let size = object.size();
+        let (object, status) = match range {
+            None if *method == Method::Head => {
+                return Ok(Some(Response::builder().with_headers(headers).empty()));
+            }
+            None => (object, 200),
+            Some(range) => {
+                let resolved = match range.ranges() {
+                    [spec] => spec.resolve(size),
+                    _ => None,
+                };
+                let Some((start, end)) = resolved else {
+                    headers.set("Content-Range", &format!("bytes */{size}"))?;
+                    return Ok(Some(
+                        Response::builder()
+                            .with_status(416)
+                            .with_headers(headers)
+                            .empty(),
+                    ));
+                };
+                let object = self
+                    .bucket
+                    .get(&self.key)
+                    .only_if(Conditional {
+                        etag_matches: Some(etag.tag().to_string()),
+                        ..Default::default()
+                    })
+                    .range(Range::OffsetWithLength {
+                        offset: start,
+                        length: end - start + 1,
+                    })
+                    .execute()
+                    .await?;
+                let Some(object) = object else {
+                    return Response::error("Not Found", 404).map(Some);
+                };
+                // R2 leaves out the body when the object no longer has the ETag of the head.
+                if object.body().is_none() {
+                    return Ok(None);
+                }
+                headers.set("Content-Range", &format!("bytes {start}-{end}/{size}"))?;
+                (object, 206)
+            }
+        };
+
+        let body = match object.body() {
+            Some(body) => body.response_body()?,
+            None => ResponseBody::Empty,
+        };
+        Ok(Some(
+            Response::builder()
+                .with_status(status)
+                .with_headers(headers)
+                .body(body),
+        ))
+    }
+
+    fn headers(&self, object: &Object, etag: &ETag, last_modified: u64) -> Result<Headers> {
+        let headers = Headers::new();
+        object.write_http_metadata(headers.clone())?;
+        headers.set_header(etag)?;
+        headers.set("Last-Modified", &http_date(last_modified))?;
+        headers.set("Accept-Ranges", "bytes")?;
+        if let Some(cache_control) = &self.cache_control {
+            if !headers.has("Cache-Control")? {
+                headers.set("Cache-Control", cache_control)?;
+            }
+        }
+        Ok(headers)
+    }
+}
+
+/// The conditional headers of a request, evaluated in the order given by RFC 9110 section 13.2.2.
+#[derive(Debug, Default)]
+struct Preconditions {
+    if_match: Option<IfMatch>,
+    if_none_match: Option<IfNoneMatch>,
+    if_modified_since: Option<u64>,
+    if_unmodified_since: Option<u64>,
+}
+
+impl Preconditions {
+    fn from_headers(headers: &Headers) -> Result<Self> {
+        Ok(Self {
+            // Malformed entity tags are ignored, like malformed dates.
+            if_match: headers.header().ok().flatten(),
+            if_none_match: headers.header().ok().flatten(),
+            if_modified_since: headers
+                .get("If-Modified-Since")?
+                .and_then(|h| parse_http_date(&h)),
+            if_unmodified_since: headers
+                .get("If-Unmodified-Since")?
+                .and_then(|h| parse_http_date(&h)),
+        })
+    }
+
+    /// The status to respond with instead of the object, if a precondition fails for an object
+    /// with the given `ETag` and last modification time in seconds.
+    fn evaluate(&self, etag: &ETag, last_modified: u64) -> Option<u16> {
+        match &self.if_match {
+            Some(if_match) if !if_match.matches(etag) => return Some(412),
+            Some(_) => {}
+            None => {
+                if matches!(self.if_unmodified_since, Some(since) if last_modified > since) {
+                    return Some(412);
+                }
+            }
+        }
+
+        match &self.if_none_match {
+            Some(if_none_match) if if_none_match.matches(etag) => Some(304),
+            Some(_) => None,
+            None => match self.if_modified_since {
+                Some(since) if last_modified <= since => Some(304),
+                _ => None,
+            },
+        }
+    }
+
+    /// The part of these preconditions R2 can check itself, so that it doesn't read the body of
+    /// an object that won't be sent. Dates aren't included: R2 compares them with millisecond
+    /// precision while HTTP dates are only precise to the second.
+    fn conditional(&self) -> Conditional {
+        let single = |tags: &[ETag]| match tags {
+            [tag] => Some(tag.tag().to_string()),
+            _ => None,
+        };
+        Conditional {
+            etag_matches: match &self.if_match {
+                Some(IfMatch::Tags(tags)) => single(tags),
+                _ => None,
+            },
+            etag_does_not_match: match &self.if_none_match {
+                Some(IfNoneMatch::Tags(tags)) => single(tags),
+                _ => None,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_header<H: TypedHeader>(value: &str) -> H {
+        H::parse(value).unwrap()
+    }
+
+    #[test]
+    fn evaluates_preconditions() {
+        let etag = &ETag::strong("abc");
+        let modified = 784111777;
+
+        let none = Preconditions::default();
+        assert_eq!(none.evaluate(etag, modified), None);
+
+        let cached = Preconditions {
+            if_none_match: Some(parse_header("\"xyz\", W/\"abc\"")),
+            if_modified_since: Some(modified + 10),
+            ..Default::default()
+        };
+        assert_eq!(cached.evaluate(etag, modified), Some(304));
+
+        let stale = Preconditions {
+            if_none_match: Some(parse_header("\"xyz\"")),
+            if_modified_since: Some(modified),
+            ..Default::default()
+        };
+        assert_eq!(stale.evaluate(etag, modified), None);
+
+        let by_date = Preconditions {
+            if_modified_since: Some(modified),
+            ..Default::default()
+        };
+        assert_eq!(by_date.evaluate(etag, modified), Some(304));
+        assert_eq!(by_date.evaluate(etag, modified + 1), None);
+
+        let weak_match = Preconditions {
+            if_match: Some(parse_header("W/\"abc\"")),
+            ..Default::default()
+        };
+        assert_eq!(weak_match.evaluate(etag, modified), Some(412));
+
+        let unmodified = Preconditions {
+            if_unmodified_since: Some(modified - 1),
+            ..Default::default()
+        };
+        assert_eq!(unmodified.evaluate(etag, modified), Some(412));
+    }
+
+    #[test]
+    fn only_single_etags_are_checked_by_r2() {
+        let pre = Preconditions {
+            if_match: Some(parse_header("\"abc\"")),
+            if_none_match: Some(parse_header("\"a\", \"b\"")),
+            if_modified_since: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            pre.conditional(),
+            Conditional {
+                etag_matches: Some("abc".into()),
+                ..Default::default()
+            }
+        );
+
+        let pre = Preconditions {
+            if_none_match: Some(parse_header("W/\"abc\"")),
+            ..Default::default()
+        };
+        assert_eq!(pre.conditional().etag_does_not_match, Some("abc".into()));
+    }
+}
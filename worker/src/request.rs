@@ -1,9 +1,11 @@
 use std::convert::TryFrom;
 
 use crate::{
-    cf::Cf, error::Error, headers::Headers, http::Method, ByteStream, FormData, RequestInit, Result,
+    cf::Cf, error::Error, headers::Headers, http::Method, multipart, ByteStream, CookieJar,
+    FormData, Multipart, RequestInit, Result,
 };
 
+use futures_util::TryStreamExt;
 use js_sys::futures::JsFuture;
 use serde::de::DeserializeOwned;
 #[cfg(test)]
@@ -24,6 +26,7 @@ pub struct Request {
     cf: Option<Cf>,
     edge_request: web_sys::Request,
     body_used: bool,
+    body_limit: Option<usize>,
     immutable: bool,
 }
 
@@ -56,6 +59,7 @@ impl From<web_sys::Request> for Request {
             cf: req.cf().map(Into::into),
             edge_request: req,
             body_used: false,
+            body_limit: None,
             immutable: true,
         }
     }
@@ -134,8 +138,24 @@ impl Request {
             })
     }
 
+    /// Limit how many bytes [`json`](Request::json), [`text`](Request::text),
+    /// [`bytes`](Request::bytes) and [`form_data`](Request::form_data) read from the body, as the
+    /// `*_limited` variants of those methods do. [`Router::body_limit`](crate::Router::body_limit)
+    /// sets this for every request it handles.
+    pub fn set_body_limit(&mut self, limit: Option<usize>) {
+        self.body_limit = limit;
+    }
+
+    /// The limit set with [`set_body_limit`](Request::set_body_limit), if any.
+    pub fn body_limit(&self) -> Option<usize> {
+        self.body_limit
+    }
+
     /// Access this request's body encoded as JSON.
     pub async fn json<B: DeserializeOwned>(&mut self) -> Result<B> {
+        if let Some(limit) = self.body_limit {
+            return self.json_limited(limit).await;
+        }
         if !self.body_used {
             self.body_used = true;
             return JsFuture::from(self.edge_request.json()?)
@@ -154,6 +174,9 @@ impl Request {
 
     /// Access this request's body as plaintext.
     pub async fn text(&mut self) -> Result<String> {
+        if let Some(limit) = self.body_limit {
+            return self.text_limited(limit).await;
+        }
         if !self.body_used {
             self.body_used = true;
             return JsFuture::from(self.edge_request.text()?)
@@ -172,6 +195,9 @@ impl Request {
 
     /// Access this request's body as raw bytes.
     pub async fn bytes(&mut self) -> Result<Vec<u8>> {
+        if let Some(limit) = self.body_limit {
+            return self.bytes_limited(limit).await;
+        }
         if !self.body_used {
             self.body_used = true;
             return JsFuture::from(self.edge_request.array_buffer()?)
@@ -190,6 +216,9 @@ impl Request {
 
     /// Access this request's body as a form-encoded payload and pull out fields and files.
     pub async fn form_data(&mut self) -> Result<FormData> {
+        if let Some(limit) = self.body_limit {
+            return self.form_data_limited(limit).await;
+        }
         if !self.body_used {
             self.body_used = true;
             return JsFuture::from(self.edge_request.form_data()?)
@@ -206,6 +235,87 @@ impl Request {
         Err(Error::BodyUsed)
     }
 
+    /// Access this request's body as raw bytes, reading at most `limit` bytes. Fails with
+    /// [`Error::PayloadTooLarge`] without reading the body if its `Content-Length` exceeds
+    /// `limit`, and as soon as more than `limit` bytes have been read otherwise.
+    pub async fn bytes_limited(&mut self, limit: usize) -> Result<Vec<u8>> {
+        if self.body_used {
+            return Err(Error::BodyUsed);
+        }
+        let content_length = self.headers.get("Content-Length")?;
+        if content_length
+            .and_then(|length| length.trim().parse::<u64>().ok())
+            .is_some_and(|length| length > limit as u64)
+        {
+            return Err(Error::PayloadTooLarge { limit });
+        }
+        if self.edge_request.body().is_none() {
+            self.body_used = true;
+            return Ok(Vec::new());
+        }
+
+        let mut stream = self.stream()?;
+        let mut bytes = Vec::new();
+        while let Some(chunk) = stream.try_next().await? {
+            if bytes.len() + chunk.len() > limit {
+                return Err(Error::PayloadTooLarge { limit });
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    /// Access this request's body as plaintext, reading at most `limit` bytes. See
+    /// [`bytes_limited`](Request::bytes_limited).
+    pub async fn text_limited(&mut self, limit: usize) -> Result<String> {
+        let bytes = self.bytes_limited(limit).await?;
+        String::from_utf8(bytes).map_err(|e| Error::RustError(e.to_string()))
+    }
+
+    /// Access this request's body encoded as JSON, reading at most `limit` bytes. See
+    /// [`bytes_limited`](Request::bytes_limited).
+    pub async fn json_limited<B: DeserializeOwned>(&mut self, limit: usize) -> Result<B> {
+        let bytes = self.bytes_limited(limit).await?;
+        serde_json::from_slice(&bytes).map_err(Error::from)
+    }
+
+    /// Access this request's body as a form-encoded payload, reading at most `limit` bytes. See
+    /// [`bytes_limited`](Request::bytes_limited).
+    pub async fn form_data_limited(&mut self, limit: usize) -> Result<FormData> {
+        let bytes = self.bytes_limited(limit).await?;
+
+        // Let the runtime parse the buffered body, which needs the boundary in its content type.
+        let headers = web_sys::Headers::new()?;
+        if let Some(content_type) = self.headers.get("Content-Type")? {
+            headers.set("Content-Type", &content_type)?;
+        }
+        let init = web_sys::ResponseInit::new();
+        init.set_headers(&headers);
+        let body = js_sys::Uint8Array::from(bytes.as_slice());
+        let response = web_sys::Response::new_with_opt_buffer_source_and_init(Some(&body), &init)?;
+        JsFuture::from(response.form_data()?)
+            .await
+            .map(|val| val.into())
+            .map_err(|e| {
+                Error::JsError(
+                    e.as_string()
+                        .unwrap_or_else(|| "failed to get form data from request".into()),
+                )
+            })
+    }
+
+    /// Access this request's `multipart/form-data` body as a [`Multipart`] stream of parts,
+    /// without buffering it into memory. Returns [`Error::BadEncoding`] if the request's
+    /// `Content-Type` isn't `multipart/*` with a boundary.
+    pub fn multipart(&mut self) -> Result<Multipart> {
+        let boundary = self
+            .headers
+            .get("Content-Type")?
+            .and_then(|content_type| multipart::boundary(&content_type))
+            .ok_or(Error::BadEncoding)?;
+        Ok(Multipart::new(self.stream()?, boundary))
+    }
+
     /// Access this request's body as a [`Stream`](futures::stream::Stream) of bytes.
     pub fn stream(&mut self) -> Result<ByteStream> {
         if self.body_used {
@@ -230,6 +340,15 @@ impl Request {
         &self.headers
     }
 
+    /// Parse the cookies sent with this request from its `Cookie` header.
+    pub fn cookies(&self) -> Result<CookieJar> {
+        Ok(self
+            .headers
+            .get("Cookie")?
+            .map(|header| CookieJar::parse(&header))
+            .unwrap_or_default())
+    }
+
     /// Get a mutable reference to this request's `Headers`.
     /// **Note:** they can only be modified if the request was created from scratch or cloned.
     pub fn headers_mut(&mut self) -> Result<&mut Headers> {
@@ -289,20 +408,32 @@ impl Request {
         Q::deserialize(deserializer).map_err(Error::from)
     }
 
+    /// Clone this request, including its [body limit](Request::set_body_limit).
     #[allow(clippy::should_implement_trait)]
     pub fn clone(&self) -> Result<Self> {
-        self.edge_request
-            .clone()
-            .map(|req| req.into())
-            .map_err(Error::from)
+        let mut req: Request = self.edge_request.clone()?.into();
+        req.body_limit = self.body_limit;
+        Ok(req)
     }
 
+    /// Clone this request into one whose headers can be modified, including its
+    /// [body limit](Request::set_body_limit).
     pub fn clone_mut(&self) -> Result<Self> {
         let mut req: Request = web_sys::Request::new_with_request(&self.edge_request)?.into();
         req.immutable = false;
+        req.body_limit = self.body_limit;
         Ok(req)
     }
 
+    /// This request if its headers can be modified, or a mutable copy of it.
+    pub(crate) fn into_mut(self) -> Result<Self> {
+        if self.immutable {
+            self.clone_mut()
+        } else {
+            Ok(self)
+        }
+    }
+
     pub fn inner(&self) -> &web_sys::Request {
         &self.edge_request
     }
@@ -1,3 +1,4 @@
+use crate::cookie::Cookie;
 use crate::cors::Cors;
 use crate::error::Error;
 use crate::headers::Headers;
@@ -381,6 +382,13 @@ impl ResponseBuilder {
         Ok(self)
     }
 
+    /// Add a `Set-Cookie` header setting `cookie`. Fails if the cookie is invalid, see
+    /// [`Cookie::to_header`].
+    pub fn with_cookie(self, cookie: &Cookie) -> Result<Self> {
+        self.headers.append("Set-Cookie", &cookie.to_header()?)?;
+        Ok(self)
+    }
+
     /// Sets this response's cors headers from the `Cors` struct.
     /// Example usage:
     /// ```
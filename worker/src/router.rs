@@ -1,21 +1,88 @@
-use std::{collections::HashMap, future::Future, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, future::Future, rc::Rc};
 
-use futures_util::future::LocalBoxFuture;
+use futures_util::future::{self, LocalBoxFuture};
 use matchit::{Match, Router as MatchItRouter};
 
 use crate::{
+    assets::Assets,
+    cors::Cors,
     durable::ObjectNamespace,
     env::{Env, Secret, Var},
+    extract::{FromRouteRequest, RouteHandler},
     http::Method,
+    middleware::{Middleware, Next},
     rate_limit::RateLimiter,
     request::Request,
     response::Response,
-    Bucket, Fetcher, KvStore, Result,
+    Bucket, Error, Fetcher, KvStore, Result,
 };
 
+/// Name of the catch-all parameter used to capture the remainder of the path below a mounted
+/// router's prefix. It is removed before the mounted router sees the parameters.
+const MOUNT_PATH_PARAM: &str = "__worker_mount_path";
+
 type HandlerFn<D> = fn(Request, RouteContext<D>) -> Result<Response>;
 type AsyncHandlerFn<'a, D> =
     Rc<dyn 'a + Fn(Request, RouteContext<D>) -> LocalBoxFuture<'a, Result<Response>>>;
+type ErrorHandlerFn<'a> = Box<dyn 'a + Fn(Error) -> Result<Response>>;
+
+/// A route registered on a [`Router`], as listed by [`Router::routes`].
+#[derive(Debug, Clone)]
+pub struct RouteInfo {
+    method: Method,
+    pattern: String,
+    params: Vec<String>,
+    #[cfg(feature = "openapi")]
+    doc: Option<crate::openapi::RouteDoc>,
+}
+
+impl RouteInfo {
+    fn new(method: Method, pattern: String) -> Self {
+        Self {
+            method,
+            params: pattern_params(&pattern),
+            pattern,
+            #[cfg(feature = "openapi")]
+            doc: None,
+        }
+    }
+
+    /// The HTTP method the route responds to.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The pattern the route was registered with, including the prefix of any router it was
+    /// mounted on.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The names of the match and wildcard placeholders in the route's pattern.
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// The documentation attached to the route with [`Router::describe`].
+    ///
+    /// **Requires** `openapi` feature.
+    #[cfg(feature = "openapi")]
+    pub fn doc(&self) -> Option<&crate::openapi::RouteDoc> {
+        self.doc.as_ref()
+    }
+}
+
+/// Extract the placeholder names of a pattern, e.g. `["id", "path"]` for `/user/:id/*path`.
+fn pattern_params(pattern: &str) -> Vec<String> {
+    pattern
+        .split('/')
+        .filter_map(|segment| {
+            segment
+                .find([':', '*'])
+                .map(|i| segment[i + 1..].to_string())
+        })
+        .collect()
+}
 
 /// Represents the URL parameters parsed from the path, e.g. a route with "/user/:id" pattern would
 /// contain a single "id" key.
@@ -26,6 +93,16 @@ impl RouteParams {
     fn get(&self, key: &str) -> Option<&String> {
         self.0.get(key)
     }
+
+    pub(crate) fn inner(&self) -> &HashMap<String, String> {
+        &self.0
+    }
+
+    fn extend(&mut self, params: matchit::Params) {
+        for (ident, value) in params.iter() {
+            self.0.insert(ident.into(), value.into());
+        }
+    }
 }
 
 enum Handler<'a, D> {
@@ -42,10 +119,61 @@ impl<D> Clone for Handler<'_, D> {
     }
 }
 
+type MiddlewareStack<'a, D> = Rc<[Rc<dyn Middleware<D> + 'a>]>;
+
+/// A registered handler along with the group middleware that wraps it.
+struct Route<'a, D> {
+    handler: Handler<'a, D>,
+    middleware: MiddlewareStack<'a, D>,
+}
+
+impl<D> Clone for Route<'_, D> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: self.handler.clone(),
+            middleware: self.middleware.clone(),
+        }
+    }
+}
+
+impl<'a, D: 'a> Route<'a, D> {
+    async fn call(&self, req: Request, ctx: RouteContext<D>) -> Result<Response> {
+        let endpoint = |req, ctx| -> LocalBoxFuture<'_, Result<Response>> {
+            match &self.handler {
+                Handler::Sync(func) => Box::pin(future::ready((func)(req, ctx))),
+                Handler::Async(func) => (func)(req, ctx),
+            }
+        };
+        Next::new(&self.middleware, &endpoint).run(req, ctx).await
+    }
+}
+
+type NodeWithHandlers<'a, D> = MatchItRouter<Route<'a, D>>;
+
+/// The route tables of a `Router`, along with the configuration used when no route matches.
+struct Routes<'a, D> {
+    handlers: HashMap<Method, NodeWithHandlers<'a, D>>,
+    or_else_any_method: NodeWithHandlers<'a, D>,
+    not_found: Option<Route<'a, D>>,
+    method_not_allowed: Option<Route<'a, D>>,
+    preflight: Option<Cors>,
+}
+
 /// A path-based HTTP router supporting exact-match or wildcard placeholders and shared data.
+///
+/// Requests for a registered path with a method that has no handler are answered automatically:
+/// HEAD falls back to the GET handler with the body dropped, OPTIONS responds with the registered
+/// methods in an `Allow` header (see also [`Router::cors_preflight`]), and other methods receive a
+/// `405 Method Not Allowed` response with an `Allow` header.
 pub struct Router<'a, D> {
-    handlers: HashMap<Method, MatchItRouter<Handler<'a, D>>>,
-    or_else_any_method: MatchItRouter<Handler<'a, D>>,
+    routes: Routes<'a, D>,
+    middleware: Vec<Rc<dyn Middleware<D> + 'a>>,
+    group_middleware: Vec<Rc<dyn Middleware<D> + 'a>>,
+    error_handler: Option<ErrorHandlerFn<'a>>,
+    body_limit: Option<usize>,
+    registered: Vec<RouteInfo>,
+    #[cfg(feature = "openapi")]
+    openapi: Option<(String, String, String)>,
     data: D,
 }
 
@@ -90,6 +218,11 @@ impl<D> RouteContext<D> {
         self.params.get(key)
     }
 
+    /// Get all URL parameters parsed by the router.
+    pub fn params(&self) -> &RouteParams {
+        &self.params
+    }
+
     /// Get a [Service Binding](https://developers.cloudflare.com/workers/runtime-apis/service-bindings/)
     /// for Worker-to-Worker communication.
     pub fn service(&self, binding: &str) -> Result<Fetcher> {
@@ -125,12 +258,155 @@ impl<'a, D: 'a> Router<'a, D> {
     /// Construct a new `Router` with arbitrary data that will be available to your various routes.
     pub fn with_data(data: D) -> Self {
         Self {
-            handlers: HashMap::new(),
-            or_else_any_method: MatchItRouter::new(),
+            routes: Routes {
+                handlers: HashMap::new(),
+                or_else_any_method: MatchItRouter::new(),
+                not_found: None,
+                method_not_allowed: None,
+                preflight: None,
+            },
+            middleware: Vec::new(),
+            group_middleware: Vec::new(),
+            error_handler: None,
+            body_limit: None,
+            registered: Vec::new(),
+            #[cfg(feature = "openapi")]
+            openapi: None,
             data,
         }
     }
 
+    /// Attach middleware that runs for every request handled by this `Router`, including
+    /// requests that end up with a "Not Found" or "Method Not Allowed" response. Middleware runs
+    /// in the order it is attached.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// # async fn run(req: Request, env: Env) -> Result<Response> {
+    /// Router::new()
+    ///     .middleware(Cors::new().with_origins(["*"]))
+    ///     .get("/", |_, _| Response::ok("Hello"))
+    ///     .run(req, env)
+    ///     .await
+    /// # }
+    /// ```
+    pub fn middleware(mut self, middleware: impl Middleware<D> + 'a) -> Self {
+        self.middleware.push(Rc::new(middleware));
+        self
+    }
+
+    /// Attach middleware to a group of routes. Only the routes registered inside `routes` are
+    /// wrapped by `middleware`; groups can be nested, in which case the outer group's middleware
+    /// runs first.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// # struct RequireToken;
+    /// # #[async_trait::async_trait(?Send)]
+    /// # impl<D> Middleware<D> for RequireToken {}
+    /// # async fn run(req: Request, env: Env) -> Result<Response> {
+    /// Router::new()
+    ///     .get("/", |_, _| Response::ok("public"))
+    ///     .group(RequireToken, |router| {
+    ///         router.get("/admin", |_, _| Response::ok("private"))
+    ///     })
+    ///     .run(req, env)
+    ///     .await
+    /// # }
+    /// ```
+    pub fn group(
+        mut self,
+        middleware: impl Middleware<D> + 'a,
+        routes: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        self.group_middleware.push(Rc::new(middleware));
+        let mut router = routes(self);
+        router.group_middleware.pop();
+        router
+    }
+
+    /// Answer CORS preflight requests for registered paths from the given policy. Preflights for
+    /// paths without an explicit OPTIONS handler receive a `204 No Content` response carrying the
+    /// policy's headers; if the policy doesn't list any methods, the methods registered for the
+    /// path are advertised instead.
+    ///
+    /// Regardless of this setting, OPTIONS requests for paths without an explicit OPTIONS handler
+    /// are answered with the registered methods in an `Allow` header, and HEAD requests fall back
+    /// to the path's GET handler with the body dropped.
+    pub fn cors_preflight(mut self, cors: Cors) -> Self {
+        self.routes.preflight = Some(cors);
+        self
+    }
+
+    /// Mount another `Router` under a path prefix, e.g. `/api/v1`. Requests whose path starts with
+    /// the prefix are handed to the mounted router with the prefix stripped, so a route registered
+    /// as `/users/:id` on the mounted router responds to `/api/v1/users/:id`.
+    ///
+    /// The mounted router keeps its own shared data and middleware; middleware attached to this
+    /// router (globally or through [`group`](Router::group)) runs first. The prefix may contain
+    /// parameters, which are available from [`RouteContext::param`] in the mounted router's
+    /// handlers.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// fn users() -> Router<'static, ()> {
+    ///     Router::new().get("/:id", |_, ctx| {
+    ///         let tenant = ctx.param("tenant").unwrap();
+    ///         let id = ctx.param("id").unwrap();
+    ///         Response::ok(format!("user {id} of {tenant}"))
+    ///     })
+    /// }
+    ///
+    /// # async fn run(req: Request, env: Env) -> Result<Response> {
+    /// Router::new()
+    ///     .mount("/tenants/:tenant/users", users())
+    ///     .run(req, env)
+    ///     .await
+    /// # }
+    /// ```
+    pub fn mount<D2: 'a>(mut self, prefix: &str, router: Router<'a, D2>) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        self.registered
+            .extend(router.registered.iter().map(|route| {
+                RouteInfo {
+                    pattern: match route.pattern.as_str() {
+                        "/" if !prefix.is_empty() => prefix.to_string(),
+                        pattern => format!("{prefix}{pattern}"),
+                    },
+                    params: pattern_params(prefix)
+                        .into_iter()
+                        .chain(route.params.iter().cloned())
+                        .collect(),
+                    ..route.clone()
+                }
+            }));
+        let mounted = Rc::new(RefCell::new(Some(router)));
+        let handler = Handler::Async(Rc::new(move |req, route_info: RouteContext<D>| {
+            let mounted = mounted.clone();
+            Box::pin(async move {
+                let router = mounted.borrow_mut().take().ok_or(Error::RouteNoDataError)?;
+                let mut params = route_info.params;
+                let path = match params.0.remove(MOUNT_PATH_PARAM) {
+                    Some(rest) => format!("/{rest}"),
+                    None => "/".to_string(),
+                };
+                router.run_at(req, route_info.env, path, params).await
+            })
+        }));
+
+        let mut patterns = vec![
+            format!("{prefix}/"),
+            format!("{prefix}/*{MOUNT_PATH_PARAM}"),
+        ];
+        if !prefix.is_empty() {
+            patterns.push(prefix.to_string());
+        }
+        for pattern in patterns {
+            self.insert_handler(&pattern, handler.clone(), Method::all());
+        }
+        self
+    }
+
     /// Register an HTTP handler that will exclusively respond to HEAD requests.
     pub fn head(mut self, pattern: &str, func: HandlerFn<D>) -> Self {
         self.add_handler(pattern, Handler::Sync(func), vec![Method::Head]);
@@ -188,12 +464,75 @@ impl<'a, D: 'a> Router<'a, D> {
     /// Register an HTTP handler that will respond to all methods that are not handled explicitly by
     /// other handlers.
     pub fn or_else_any_method(mut self, pattern: &str, func: HandlerFn<D>) -> Self {
-        self.or_else_any_method
-            .insert(pattern, Handler::Sync(func))
+        let route = self.new_route(Handler::Sync(func));
+        self.routes
+            .or_else_any_method
+            .insert(pattern, route)
             .unwrap_or_else(|e| panic!("failed to register route for {pattern} pattern: {e}"));
         self
     }
 
+    /// Register an HTTP handler that will respond to requests that don't match any route, instead
+    /// of the default `404 Not Found` response.
+    pub fn not_found(mut self, func: HandlerFn<D>) -> Self {
+        self.routes.not_found = Some(self.new_route(Handler::Sync(func)));
+        self
+    }
+
+    /// Register an HTTP handler that will respond to requests for a registered path with a method
+    /// that has no handler, instead of the default `405 Method Not Allowed` response. An `Allow`
+    /// header listing the registered methods is added to the response unless the handler sets
+    /// one.
+    pub fn method_not_allowed(mut self, func: HandlerFn<D>) -> Self {
+        self.routes.method_not_allowed = Some(self.new_route(Handler::Sync(func)));
+        self
+    }
+
+    /// Register a function that converts an `Err` returned by a handler or middleware into a
+    /// `Response`, e.g. to render a consistent error body. Without it, errors are returned from
+    /// [`Router::run`] as-is. Errors from a mounted router that has no error handler of its own are
+    /// passed to this one.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// # async fn run(req: Request, env: Env) -> Result<Response> {
+    /// Router::new()
+    ///     .get("/", |_, _| Err("oops".into()))
+    ///     .on_error(|err| {
+    ///         let body = serde_json::json!({ "error": err.to_string() });
+    ///         Ok(Response::from_json(&body)?.with_status(500))
+    ///     })
+    ///     .run(req, env)
+    ///     .await
+    /// # }
+    /// ```
+    pub fn on_error(mut self, func: impl Fn(Error) -> Result<Response> + 'a) -> Self {
+        self.error_handler = Some(Box::new(func));
+        self
+    }
+
+    /// Limit the size of the request bodies read by handlers with [`Request::json`],
+    /// [`Request::text`], [`Request::bytes`] and [`Request::form_data`], and by the
+    /// [`Json`](crate::Json) and [`Form`](crate::Form) extractors. Larger bodies fail with
+    /// [`Error::PayloadTooLarge`], which is answered with `413 Payload Too Large` unless an
+    /// [error handler](Router::on_error) handles it. A router mounted on this one can set its own
+    /// limit.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// # async fn run(req: Request, env: Env) -> Result<Response> {
+    /// Router::new()
+    ///     .body_limit(64 * 1024)
+    ///     .post_async("/echo", |mut req, _| async move { Response::ok(req.text().await?) })
+    ///     .run(req, env)
+    ///     .await
+    /// # }
+    /// ```
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        self.body_limit = Some(limit);
+        self
+    }
+
     /// Register an HTTP handler that will exclusively respond to HEAD requests. Enables the use of
     /// `async/await` syntax in the callback.
     pub fn head_async<T>(
@@ -348,21 +687,198 @@ impl<'a, D: 'a> Router<'a, D> {
     where
         T: Future<Output = Result<Response>> + 'a,
     {
-        self.or_else_any_method
-            .insert(
-                pattern,
-                Handler::Async(Rc::new(move |req, route| Box::pin(func(req, route)))),
-            )
+        let route = self.new_route(Handler::Async(Rc::new(move |req, route| {
+            Box::pin(func(req, route))
+        })));
+        self.routes
+            .or_else_any_method
+            .insert(pattern, route)
             .unwrap_or_else(|e| panic!("failed to register route for {pattern} pattern: {e}"));
         self
     }
 
+    /// Register an HTTP handler that will respond to requests that don't match any route, instead
+    /// of the default `404 Not Found` response. Enables the use of `async/await` syntax in the
+    /// callback.
+    pub fn not_found_async<T>(mut self, func: impl Fn(Request, RouteContext<D>) -> T + 'a) -> Self
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.routes.not_found = Some(self.new_route(Handler::Async(Rc::new(move |req, route| {
+            Box::pin(func(req, route))
+        }))));
+        self
+    }
+
+    /// Serve static assets for requests that don't match any route, in place of the
+    /// [`not_found`](Router::not_found) handler. Routes registered on the router, including
+    /// mounted routers, always take precedence over assets.
+    pub fn assets(mut self, assets: Assets) -> Self {
+        let assets = Rc::new(assets);
+        self.routes.not_found = Some(self.new_route(Handler::Async(Rc::new(
+            move |req, route: RouteContext<D>| {
+                let assets = assets.clone();
+                Box::pin(async move { assets.serve(req, &route.env).await })
+            },
+        ))));
+        self
+    }
+
+    /// Register an HTTP handler that will respond to requests for a registered path with a method
+    /// that has no handler, instead of the default `405 Method Not Allowed` response. An `Allow`
+    /// header listing the registered methods is added to the response unless the handler sets
+    /// one. Enables the use of `async/await` syntax in the callback.
+    pub fn method_not_allowed_async<T>(
+        mut self,
+        func: impl Fn(Request, RouteContext<D>) -> T + 'a,
+    ) -> Self
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.routes.method_not_allowed =
+            Some(self.new_route(Handler::Async(Rc::new(move |req, route| {
+                Box::pin(func(req, route))
+            }))));
+        self
+    }
+
+    /// Register an HTTP handler for `method` whose arguments are typed extractors, such as
+    /// [`Json`](crate::Json), [`Query`](crate::Query), [`Path`](crate::Path),
+    /// [`Form`](crate::Form), [`Headers`](crate::Headers) or [`Env`]. If any extractor fails, the
+    /// handler is not called and the client receives a `400 Bad Request` response describing the
+    /// failure.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// #[derive(serde::Deserialize)]
+    /// struct Account {
+    ///     id: u64,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize, serde::Serialize)]
+    /// struct Zone {
+    ///     name: String,
+    /// }
+    ///
+    /// async fn create_zone(Path(account): Path<Account>, Json(zone): Json<Zone>) -> Result<Response> {
+    ///     Response::from_json(&zone)
+    /// }
+    ///
+    /// # async fn run(req: Request, env: Env) -> Result<Response> {
+    /// Router::new()
+    ///     .route(Method::Post, "/account/:id/zones", create_zone)
+    ///     .run(req, env)
+    ///     .await
+    /// # }
+    /// ```
+    pub fn route<H, Args>(mut self, method: Method, pattern: &str, handler: H) -> Self
+    where
+        H: RouteHandler<Args> + 'a,
+        H::Future: 'a,
+        Args: FromRouteRequest<D> + 'a,
+    {
+        let handler = Rc::new(handler);
+        self.add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |mut req, route_info| {
+                let handler = handler.clone();
+                Box::pin(async move {
+                    match Args::from_route_request(&mut req, &route_info).await {
+                        Ok(args) => handler.call(args).await,
+                        Err(e @ Error::PayloadTooLarge { .. }) => {
+                            Response::error(e.to_string(), 413)
+                        }
+                        Err(e) => Response::error(e.to_string(), 400),
+                    }
+                })
+            })),
+            vec![method],
+        );
+        self
+    }
+
+    fn new_route(&self, handler: Handler<'a, D>) -> Route<'a, D> {
+        Route {
+            handler,
+            middleware: self.group_middleware.clone().into(),
+        }
+    }
+
+    /// List the routes registered on this `Router` and on any router mounted on it, in
+    /// registration order. Handlers registered for several methods (e.g. with [`Router::on`])
+    /// appear once per method; fallbacks such as [`Router::or_else_any_method`] are not listed.
+    pub fn routes(&self) -> &[RouteInfo] {
+        &self.registered
+    }
+
+    /// Attach documentation to the route registered for `method` and `pattern`, to be included
+    /// in the router's OpenAPI document.
+    ///
+    /// **Requires** `openapi` feature.
+    ///
+    /// ```no_run
+    /// # use worker::*;
+    /// # fn handler(_: Request, _: RouteContext<()>) -> Result<Response> { todo!() }
+    /// let router = Router::new().get("/user/:id", handler).describe(
+    ///     Method::Get,
+    ///     "/user/:id",
+    ///     RouteDoc::new()
+    ///         .summary("Get a user")
+    ///         .param("id", serde_json::json!({ "type": "integer" }))
+    ///         .json_response(200, "The user", serde_json::json!({ "type": "object" })),
+    /// );
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn describe(
+        mut self,
+        method: Method,
+        pattern: &str,
+        doc: crate::openapi::RouteDoc,
+    ) -> Self {
+        let route = self
+            .registered
+            .iter_mut()
+            .find(|route| route.method == method && route.pattern == pattern)
+            .unwrap_or_else(|| panic!("no {method:?} route registered for {pattern} pattern"));
+        route.doc = Some(doc);
+        self
+    }
+
+    /// Build an OpenAPI 3.0 document describing the routes of this `Router`.
+    ///
+    /// **Requires** `openapi` feature.
+    #[cfg(feature = "openapi")]
+    pub fn openapi(&self, title: &str, version: &str) -> serde_json::Value {
+        crate::openapi::document(&self.registered, title, version)
+    }
+
+    /// Serve the router's OpenAPI document as JSON in response to GET requests for `path`. The
+    /// document is built when the router runs, so it includes routes registered after this call.
+    ///
+    /// **Requires** `openapi` feature.
+    #[cfg(feature = "openapi")]
+    pub fn serve_openapi(mut self, path: &str, title: &str, version: &str) -> Self {
+        self.openapi = Some((path.into(), title.into(), version.into()));
+        self
+    }
+
     fn add_handler(&mut self, pattern: &str, func: Handler<'a, D>, methods: Vec<Method>) {
+        self.registered.extend(
+            methods
+                .iter()
+                .map(|method| RouteInfo::new(method.clone(), pattern.into())),
+        );
+        self.insert_handler(pattern, func, methods);
+    }
+
+    fn insert_handler(&mut self, pattern: &str, func: Handler<'a, D>, methods: Vec<Method>) {
+        let route = self.new_route(func);
         for method in methods {
-            self.handlers
+            self.routes
+                .handlers
                 .entry(method.clone())
                 .or_default()
-                .insert(pattern, func.clone())
+                .insert(pattern, route.clone())
                 .unwrap_or_else(|e| {
                     panic!("failed to register {method:?} route for {pattern} pattern: {e}")
                 });
@@ -371,60 +887,168 @@ impl<'a, D: 'a> Router<'a, D> {
 
     /// Handle the request provided to the `Router` and return a `Future`.
     pub async fn run(self, req: Request, env: Env) -> Result<Response> {
-        let (handlers, data, or_else_any_method_handler) = self.split();
-
-        if let Some(handlers) = handlers.get(&req.method()) {
-            if let Ok(Match { value, params }) = handlers.at(&req.path()) {
-                let route_info = RouteContext {
-                    data,
-                    env,
-                    params: params.into(),
-                };
-                return match value {
-                    Handler::Sync(func) => (func)(req, route_info),
-                    Handler::Async(func) => (func)(req, route_info).await,
-                };
-            }
+        let path = req.path();
+        match self
+            .run_at(req, env, path, RouteParams(HashMap::new()))
+            .await
+        {
+            Err(e @ Error::PayloadTooLarge { .. }) => Response::error(e.to_string(), 413),
+            res => res,
+        }
+    }
+
+    /// Handle the request as if its path were `path`, with `params` already captured by any
+    /// router this one is mounted on.
+    async fn run_at(
+        #[allow(unused_mut)] mut self,
+        mut req: Request,
+        env: Env,
+        path: String,
+        params: RouteParams,
+    ) -> Result<Response> {
+        #[cfg(feature = "openapi")]
+        if let Some((openapi_path, title, version)) = self.openapi.take() {
+            let document = Rc::new(self.openapi(&title, &version));
+            self.insert_handler(
+                &openapi_path,
+                Handler::Async(Rc::new(move |_, _| {
+                    let document = document.clone();
+                    Box::pin(async move { Response::from_json(&*document) })
+                })),
+                vec![Method::Get],
+            );
+        }
+
+        let Router {
+            routes,
+            middleware,
+            error_handler,
+            body_limit,
+            data,
+            ..
+        } = self;
+
+        if body_limit.is_some() {
+            req.set_body_limit(body_limit);
+        }
+
+        let route_info = RouteContext { data, env, params };
+        let endpoint = |req, route_info| -> LocalBoxFuture<'_, Result<Response>> {
+            Box::pin(routes.dispatch(&path, req, route_info))
+        };
+        match Next::new(&middleware, &endpoint).run(req, route_info).await {
+            Err(e) => match error_handler {
+                Some(handler) => handler(e),
+                None => Err(e),
+            },
+            res => res,
+        }
+    }
+}
+
+impl<'a, D: 'a> Routes<'a, D> {
+    async fn dispatch(
+        &self,
+        path: &str,
+        req: Request,
+        mut route_info: RouteContext<D>,
+    ) -> Result<Response> {
+        let method = req.method();
+
+        if let Some(Match { value, params }) = self.find(&method, path) {
+            route_info.params.extend(params);
+            return value.call(req, route_info).await;
         }
 
-        for method in Method::all() {
-            if method == Method::Head || method == Method::Options || method == Method::Trace {
-                continue;
+        if method == Method::Head {
+            if let Some(Match { value, params }) = self.find(&Method::Get, path) {
+                route_info.params.extend(params);
+                let (builder, _) = value.call(req, route_info).await?.into_parts();
+                return Ok(builder.empty());
             }
-            if let Some(handlers) = handlers.get(&method) {
-                if let Ok(Match { .. }) = handlers.at(&req.path()) {
-                    return Response::error("Method Not Allowed", 405);
+        }
+
+        let allowed = self.allowed_methods(path);
+        let allow = allowed
+            .iter()
+            .map(|method| method.as_ref())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if method == Method::Options && !allowed.is_empty() {
+            if let Some(cors) = &self.preflight {
+                if req.headers().has("Access-Control-Request-Method")? {
+                    return cors.preflight_response(&allowed);
                 }
             }
+            let res = Response::empty()?.with_status(204);
+            res.headers().set("Allow", &allow)?;
+            return Ok(res);
         }
 
-        if let Ok(Match { value, params }) = or_else_any_method_handler.at(&req.path()) {
-            let route_info = RouteContext {
-                data,
-                env,
-                params: params.into(),
-            };
-            return match value {
-                Handler::Sync(func) => (func)(req, route_info),
-                Handler::Async(func) => (func)(req, route_info).await,
+        // Handlers for HEAD, OPTIONS and TRACE alone don't make a path "exist" for other methods,
+        // so that e.g. an OPTIONS catch-all doesn't turn every unknown path into a 405.
+        if allowed
+            .iter()
+            .any(|method| !matches!(method, Method::Head | Method::Options | Method::Trace))
+        {
+            let res = match &self.method_not_allowed {
+                Some(route) => route.call(req, route_info).await?,
+                None => Response::error("Method Not Allowed", 405)?,
             };
+            if res.headers().has("Allow")? {
+                return Ok(res);
+            }
+            // The handler's headers may be immutable, e.g. if it returned a fetched response.
+            let headers = res.headers().clone();
+            headers.set("Allow", &allow)?;
+            return Ok(res.with_headers(headers));
+        }
+
+        if let Ok(Match { value, params }) = self.or_else_any_method.at(path) {
+            route_info.params.extend(params);
+            return value.call(req, route_info).await;
+        }
+
+        match &self.not_found {
+            Some(route) => route.call(req, route_info).await,
+            None => Response::error("Not Found", 404),
         }
+    }
 
-        Response::error("Not Found", 404)
+    fn find<'m, 'p>(
+        &'m self,
+        method: &Method,
+        path: &'p str,
+    ) -> Option<Match<'m, 'p, &'m Route<'a, D>>> {
+        self.handlers.get(method)?.at(path).ok()
     }
-}
 
-type NodeWithHandlers<'a, D> = MatchItRouter<Handler<'a, D>>;
+    /// The methods a request for `path` can be answered with, in the form used by the `Allow`
+    /// header: HEAD is implied by GET, and OPTIONS by any other method.
+    fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        let registered = Method::all()
+            .into_iter()
+            .filter(|method| self.find(method, path).is_some())
+            .collect::<Vec<_>>();
+        if registered.is_empty() {
+            return registered;
+        }
 
-impl<'a, D: 'a> Router<'a, D> {
-    fn split(
-        self,
-    ) -> (
-        HashMap<Method, NodeWithHandlers<'a, D>>,
-        D,
-        NodeWithHandlers<'a, D>,
-    ) {
-        (self.handlers, self.data, self.or_else_any_method)
+        Method::all()
+            .into_iter()
+            .filter(|method| {
+                registered.contains(method)
+                    || (*method == Method::Head && registered.contains(&Method::Get))
+                    || *method == Method::Options
+            })
+            .collect()
+    }
+}
+
+impl From<HashMap<String, String>> for RouteParams {
+    fn from(params: HashMap<String, String>) -> Self {
+        RouteParams(params)
     }
 }
 
@@ -438,3 +1062,55 @@ impl From<matchit::Params<'_, '_>> for RouteParams {
         route_params
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn handler(_: Request, _: RouteContext<()>) -> Result<Response> {
+        unreachable!()
+    }
+
+    #[test]
+    fn pattern_params_lists_placeholders() {
+        assert!(pattern_params("/").is_empty());
+        assert_eq!(pattern_params("/user/:id"), ["id"]);
+        assert_eq!(
+            pattern_params("/account/:account/files/*path"),
+            ["account", "path"]
+        );
+    }
+
+    #[test]
+    fn routes_include_mounted_routers() {
+        let users = Router::new()
+            .get("/", handler)
+            .on("/:id", handler)
+            .mount("/:id/posts", Router::new().get("/:post", handler));
+        let router = Router::new()
+            .get("/health", handler)
+            .or_else_any_method("/*catchall", handler)
+            .mount("/tenants/:tenant/users/", users);
+
+        let routes = router
+            .routes()
+            .iter()
+            .map(|route| {
+                (
+                    route.method().clone(),
+                    route.pattern(),
+                    route.params().len(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(routes.len(), 2 + Method::all().len() + 1);
+        assert_eq!(routes[0], (Method::Get, "/health", 0));
+        assert_eq!(routes[1], (Method::Get, "/tenants/:tenant/users", 1));
+        assert!(routes.contains(&(Method::Delete, "/tenants/:tenant/users/:id", 2)));
+        assert_eq!(
+            routes.last().unwrap(),
+            &(Method::Get, "/tenants/:tenant/users/:id/posts/:post", 3)
+        );
+    }
+}
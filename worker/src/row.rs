@@ -0,0 +1,342 @@
+use std::rc::Rc;
+
+use crate::{Error, Result, SqlStorageValue};
+
+/// A row of a query result, along with the names of its columns, as read by
+/// [`SqlCursor::rows`](crate::SqlCursor::rows) and
+/// [`D1PreparedStatement::rows`](crate::D1PreparedStatement::rows).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlRow {
+    columns: Rc<[String]>,
+    values: Vec<SqlStorageValue>,
+}
+
+impl SqlRow {
+    pub fn new(columns: Rc<[String]>, values: Vec<SqlStorageValue>) -> Self {
+        Self { columns, values }
+    }
+
+    /// The names of the columns, in order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// The values of the columns, in order.
+    pub fn values(&self) -> &[SqlStorageValue] {
+        &self.values
+    }
+
+    fn index_of(&self, column: &str) -> Result<usize> {
+        self.columns
+            .iter()
+            .position(|name| name == column)
+            .filter(|&index| index < self.values.len())
+            .ok_or_else(|| Error::RustError(format!("no column named {column}")))
+    }
+
+    /// Convert the value of `column`. Fails if there is no such column, which is distinct from
+    /// the column being `NULL`.
+    pub fn get<T: FromSqlValue>(&self, column: &str) -> Result<T> {
+        let value = self.values[self.index_of(column)?].clone();
+        T::from_sql_value(value).map_err(|e| column_error(column, e))
+    }
+
+    /// Like [`get`](SqlRow::get), moving the value out of the row instead of copying it.
+    pub fn take<T: FromSqlValue>(&mut self, column: &str) -> Result<T> {
+        let index = self.index_of(column)?;
+        let value = std::mem::replace(&mut self.values[index], SqlStorageValue::Null);
+        T::from_sql_value(value).map_err(|e| column_error(column, e))
+    }
+
+    /// Like [`take`](SqlRow::take), for the column at `index`.
+    pub fn take_index<T: FromSqlValue>(&mut self, index: usize) -> Result<T> {
+        let value = self
+            .values
+            .get_mut(index)
+            .map(|value| std::mem::replace(value, SqlStorageValue::Null))
+            .ok_or_else(|| Error::RustError(format!("no column at index {index}")))?;
+        T::from_sql_value(value).map_err(|e| column_error(&index.to_string(), e))
+    }
+}
+
+fn column_error(column: &str, e: Error) -> Error {
+    Error::RustError(format!("column {column}: {e}"))
+}
+
+fn type_error(expected: &str, value: &SqlStorageValue) -> Error {
+    Error::RustError(format!("expected {expected}, found {value:?}"))
+}
+
+/// A type that can be built from a row of a query result, usually derived with
+/// `#[derive(FromRow)]`.
+///
+/// The derived implementation converts each field from the column with the same name, or the
+/// name given with `#[sql(rename = "...")]`. Fields marked `#[sql(skip)]` are set to their
+/// default value. Tuple structs are converted from the columns in order.
+///
+/// ```no_run
+/// # use worker::*;
+/// #[derive(FromRow, ToParams)]
+/// struct User {
+///     id: i64,
+///     #[sql(rename = "display_name")]
+///     name: String,
+///     avatar: Option<Vec<u8>>,
+/// }
+///
+/// # fn run(sql: SqlStorage, user: User) -> Result<()> {
+/// sql.exec(
+///     "INSERT INTO users (id, display_name, avatar) VALUES (?, ?, ?)",
+///     user.to_params(),
+/// )?;
+/// let users: Vec<User> = sql
+///     .exec("SELECT id, display_name, avatar FROM users", None)?
+///     .rows()?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait FromRow: Sized {
+    fn from_row(row: SqlRow) -> Result<Self>;
+}
+
+/// A type whose fields can be bound as the parameters of a query, in order, usually derived with
+/// `#[derive(ToParams)]`. Fields marked `#[sql(skip)]` are not bound.
+///
+/// The parameters can be passed to [`SqlStorage::exec`](crate::SqlStorage::exec) or bound with
+/// [`D1PreparedStatement::bind_params`](crate::D1PreparedStatement::bind_params).
+pub trait ToParams {
+    fn to_params(&self) -> Vec<SqlStorageValue>;
+}
+
+impl FromRow for SqlRow {
+    fn from_row(row: SqlRow) -> Result<Self> {
+        Ok(row)
+    }
+}
+
+/// A type that can be converted from an SQL value.
+pub trait FromSqlValue: Sized {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self>;
+}
+
+/// A type that can be converted to an SQL value.
+///
+/// It is not implemented for `u64` and `usize`, whose values don't all fit in an SQLite integer;
+/// convert them with `i64::try_from` first.
+pub trait ToSqlValue {
+    fn to_sql_value(&self) -> SqlStorageValue;
+}
+
+impl FromSqlValue for SqlStorageValue {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+impl<T: FromSqlValue> FromSqlValue for Option<T> {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        match value {
+            SqlStorageValue::Null => Ok(None),
+            value => T::from_sql_value(value).map(Some),
+        }
+    }
+}
+
+impl FromSqlValue for bool {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        match value {
+            SqlStorageValue::Boolean(b) => Ok(b),
+            // SQLite stores booleans as integers.
+            SqlStorageValue::Integer(i @ (0 | 1)) => Ok(i == 1),
+            value => Err(type_error("a boolean", &value)),
+        }
+    }
+}
+
+impl FromSqlValue for i64 {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        match value {
+            SqlStorageValue::Integer(i) => Ok(i),
+            value => Err(type_error("an integer", &value)),
+        }
+    }
+}
+
+macro_rules! from_sql_integer {
+    ($($ty:ty),*) => {$(
+        impl FromSqlValue for $ty {
+            fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+                let i = i64::from_sql_value(value)?;
+                <$ty>::try_from(i).map_err(|_| {
+                    Error::RustError(format!(
+                        "{i} is out of range for {}",
+                        stringify!($ty)
+                    ))
+                })
+            }
+        }
+    )*};
+}
+
+from_sql_integer!(i8, i16, i32, u8, u16, u32, u64, isize, usize);
+
+impl FromSqlValue for f64 {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        match value {
+            SqlStorageValue::Float(f) => Ok(f),
+            // Numbers that happen to be whole are read back as integers.
+            SqlStorageValue::Integer(i) => Ok(i as f64),
+            value => Err(type_error("a number", &value)),
+        }
+    }
+}
+
+impl FromSqlValue for f32 {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        f64::from_sql_value(value).map(|f| f as f32)
+    }
+}
+
+impl FromSqlValue for String {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        match value {
+            SqlStorageValue::String(s) => Ok(s),
+            value => Err(type_error("a string", &value)),
+        }
+    }
+}
+
+impl FromSqlValue for Vec<u8> {
+    fn from_sql_value(value: SqlStorageValue) -> Result<Self> {
+        match value {
+            SqlStorageValue::Blob(bytes) => Ok(bytes),
+            value => Err(type_error("a blob", &value)),
+        }
+    }
+}
+
+impl ToSqlValue for SqlStorageValue {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        self.clone()
+    }
+}
+
+impl<T: ToSqlValue + ?Sized> ToSqlValue for &T {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        (**self).to_sql_value()
+    }
+}
+
+impl<T: ToSqlValue> ToSqlValue for Option<T> {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        match self {
+            Some(value) => value.to_sql_value(),
+            None => SqlStorageValue::Null,
+        }
+    }
+}
+
+impl ToSqlValue for bool {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::Boolean(*self)
+    }
+}
+
+macro_rules! to_sql_integer {
+    ($($ty:ty),*) => {$(
+        impl ToSqlValue for $ty {
+            fn to_sql_value(&self) -> SqlStorageValue {
+                SqlStorageValue::Integer(i64::from(*self))
+            }
+        }
+    )*};
+}
+
+to_sql_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+impl ToSqlValue for isize {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        // `isize` is at most 64 bits wide on every supported target.
+        SqlStorageValue::Integer(*self as i64)
+    }
+}
+
+impl ToSqlValue for f64 {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::Float(*self)
+    }
+}
+
+impl ToSqlValue for f32 {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::Float(f64::from(*self))
+    }
+}
+
+impl ToSqlValue for str {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::String(self.to_string())
+    }
+}
+
+impl ToSqlValue for String {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::String(self.clone())
+    }
+}
+
+impl ToSqlValue for [u8] {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::Blob(self.to_vec())
+    }
+}
+
+impl ToSqlValue for Vec<u8> {
+    fn to_sql_value(&self) -> SqlStorageValue {
+        SqlStorageValue::Blob(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn row() -> SqlRow {
+        SqlRow::new(
+            vec!["id".to_string(), "name".to_string(), "avatar".to_string()].into(),
+            vec![
+                SqlStorageValue::Integer(7),
+                SqlStorageValue::String("Ada".into()),
+                SqlStorageValue::Null,
+            ],
+        )
+    }
+
+    #[test]
+    fn reads_columns_by_name() {
+        let mut row = row();
+        assert_eq!(row.get::<i64>("id").unwrap(), 7);
+        assert_eq!(row.get::<u8>("id").unwrap(), 7);
+        assert_eq!(row.get::<f64>("id").unwrap(), 7.0);
+        assert_eq!(row.take::<String>("name").unwrap(), "Ada");
+        assert_eq!(row.get::<Option<Vec<u8>>>("avatar").unwrap(), None);
+        assert!(row.get::<Vec<u8>>("avatar").is_err());
+        assert!(row.get::<Option<String>>("missing").is_err());
+        assert!(row.get::<String>("id").is_err());
+    }
+
+    #[test]
+    fn converts_values() {
+        assert!(bool::from_sql_value(SqlStorageValue::Integer(1)).unwrap());
+        assert!(bool::from_sql_value(SqlStorageValue::Integer(2)).is_err());
+        assert!(u8::from_sql_value(SqlStorageValue::Integer(256)).is_err());
+        assert_eq!(
+            Some("a").to_sql_value(),
+            SqlStorageValue::String("a".into())
+        );
+        assert_eq!(None::<i32>.to_sql_value(), SqlStorageValue::Null);
+        assert_eq!(5u32.to_sql_value(), SqlStorageValue::Integer(5));
+        assert_eq!((-5isize).to_sql_value(), SqlStorageValue::Integer(-5));
+        assert!(u64::from_sql_value(SqlStorageValue::Integer(-1)).is_err());
+    }
+}
@@ -1,13 +1,17 @@
 use js_sys::Array;
 use std::convert::TryFrom;
+use std::rc::Rc;
 use wasm_bindgen::{JsCast, JsValue};
-use worker_sys::types::{SqlStorage as SqlStorageSys, SqlStorageCursor as SqlStorageCursorSys};
+use worker_sys::types::{
+    DurableObjectStorage, SqlStorage as SqlStorageSys, SqlStorageCursor as SqlStorageCursorSys,
+};
 
 use serde::de::DeserializeOwned;
 use serde_wasm_bindgen as swb;
 
 use crate::Error;
 use crate::Result;
+use crate::{FromRow, SqlRow, SqlRowStream};
 
 /// A value that can be stored in Durable Object SQL storage.
 ///
@@ -157,6 +161,18 @@ impl TryFrom<JsValue> for SqlStorageValue {
                     uint8_array.copy_to(&mut bytes);
                     SqlStorageValue::Blob(bytes)
                 })
+                .or_else(|js_val| {
+                    // D1 returns blobs as arrays of bytes.
+                    if !js_sys::Array::is_array(&js_val) {
+                        return Err(js_val);
+                    }
+                    js_sys::Array::from(&js_val)
+                        .iter()
+                        .map(|byte| byte.as_f64().map(|byte| byte as u8))
+                        .collect::<Option<Vec<u8>>>()
+                        .map(SqlStorageValue::Blob)
+                        .ok_or(js_val)
+                })
                 .or_else(|js_val| {
                     js_val
                         .dyn_into::<js_sys::ArrayBuffer>()
@@ -179,14 +195,21 @@ impl TryFrom<JsValue> for SqlStorageValue {
 #[derive(Clone, Debug)]
 pub struct SqlStorage {
     inner: SqlStorageSys,
+    storage: DurableObjectStorage,
 }
 
 unsafe impl Send for SqlStorage {}
 unsafe impl Sync for SqlStorage {}
 
 impl SqlStorage {
-    pub(crate) fn new(inner: SqlStorageSys) -> Self {
-        Self { inner }
+    pub(crate) fn new(inner: SqlStorageSys, storage: DurableObjectStorage) -> Self {
+        Self { inner, storage }
+    }
+
+    /// Run `closure` synchronously in a transaction, rolling back the statements it executed if
+    /// it returns an error or panics. See [`Storage::transaction_sync`](crate::Storage::transaction_sync).
+    pub fn transaction_sync<T>(&self, closure: impl FnOnce() -> Result<T>) -> Result<T> {
+        crate::durable::transaction_sync(&self.storage, closure)
     }
 
     /// Size of the underlying SQLite database in bytes.
@@ -381,6 +404,22 @@ impl SqlCursor {
         }
     }
 
+    /// Consume the remaining rows of the cursor, converting them with [`FromRow`], usually
+    /// derived with `#[derive(FromRow)]`. Unlike [`to_array`](SqlCursor::to_array), this keeps
+    /// integers, blobs and `NULL`s distinct from other values.
+    pub fn rows<T: FromRow>(&self) -> Result<Vec<T>> {
+        let columns: Rc<[String]> = self.column_names().into();
+        self.raw()
+            .map(|values| T::from_row(SqlRow::new(columns.clone(), values?)))
+            .collect()
+    }
+
+    /// An async [`Stream`](futures_util::Stream) of the remaining rows of the cursor, which can
+    /// also be encoded into a response with [`SqlRowStream::export`].
+    pub fn stream(&self) -> SqlRowStream {
+        SqlRowStream::new(self.column_names().into(), self.raw())
+    }
+
     /// Returns a Rust iterator where each row is a Vec<SqlStorageValue>.
     ///
     /// This method provides a more efficient way to iterate over results when you
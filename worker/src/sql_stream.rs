@@ -0,0 +1,336 @@
+//! Streaming query results, row by row or encoded as NDJSON, CSV or a JSON array.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{Stream, StreamExt};
+use js_sys::Uint8Array;
+use wasm_bindgen::JsValue;
+
+use crate::{
+    base64, signal, ByteStream, Delay, Headers, Response, Result, SqlRow, SqlStorageValue,
+};
+
+/// How many rows are read between checks of [`signal::is_near_cpu_limit`].
+const YIELD_INTERVAL: usize = 256;
+
+/// The size above which an encoded chunk is emitted.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+type Rows = Box<dyn Iterator<Item = Result<Vec<SqlStorageValue>>>>;
+
+/// An async [`Stream`] of the rows of a query result, from
+/// [`SqlCursor::stream`](crate::SqlCursor::stream) or
+/// [`D1PreparedStatement::stream`](crate::D1PreparedStatement::stream).
+///
+/// When the runtime signals that the Worker is close to its CPU limit, the stream pauses between
+/// rows so pending I/O, like sending the chunks already encoded, can make progress.
+pub struct SqlRowStream {
+    columns: Rc<[String]>,
+    rows: Rows,
+    read: usize,
+    pause: Option<Delay>,
+}
+
+impl std::fmt::Debug for SqlRowStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqlRowStream")
+            .field("columns", &self.columns)
+            .field("read", &self.read)
+            .finish()
+    }
+}
+
+impl SqlRowStream {
+    pub(crate) fn new(
+        columns: Rc<[String]>,
+        rows: impl Iterator<Item = Result<Vec<SqlStorageValue>>> + 'static,
+    ) -> Self {
+        Self {
+            columns,
+            rows: Box::new(rows),
+            read: 0,
+            pause: None,
+        }
+    }
+
+    /// The names of the columns, in order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Encode the remaining rows in `format`, in chunks of about 64 KiB. A chunk is emitted early
+    /// when the Worker is close to its CPU limit.
+    pub fn export(self, format: SqlExportFormat) -> ByteStream {
+        let stream = SqlExport::new(self, format).map(|chunk| {
+            chunk
+                .map(|chunk| Uint8Array::from(chunk.as_slice()).into())
+                .map_err(JsValue::from)
+        });
+        ByteStream {
+            inner: wasm_streams::ReadableStream::from_stream(stream).into_stream(),
+        }
+    }
+
+    /// Build a `200 OK` response streaming the remaining rows in `format`, with its
+    /// `Content-Type`.
+    pub fn into_response(self, format: SqlExportFormat) -> Result<Response> {
+        let headers = Headers::new();
+        headers.set("Content-Type", format.content_type())?;
+        Ok(Response::from_stream(self.export(format))?.with_headers(headers))
+    }
+}
+
+impl Stream for SqlRowStream {
+    type Item = Result<SqlRow>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(pause) = &mut self.pause {
+            futures_util::ready!(Pin::new(pause).poll(cx));
+            self.pause = None;
+        } else if self.read > 0 && self.read % YIELD_INTERVAL == 0 && signal::is_near_cpu_limit() {
+            let mut pause = Delay::from(Duration::ZERO);
+            if Pin::new(&mut pause).poll(cx).is_pending() {
+                self.pause = Some(pause);
+                return Poll::Pending;
+            }
+        }
+
+        let columns = self.columns.clone();
+        let row = self.rows.next();
+        self.read += 1;
+        Poll::Ready(row.map(|values| Ok(SqlRow::new(columns, values?))))
+    }
+}
+
+/// The encoding of an exported query result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlExportFormat {
+    /// One JSON object per row, keyed by column name, each followed by a newline.
+    Ndjson,
+    /// A header line with the column names, then one line per row, as described by RFC 4180.
+    Csv,
+    /// A JSON array of objects keyed by column name.
+    Json,
+}
+
+impl SqlExportFormat {
+    /// The value of the `Content-Type` header for this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Encodes the rows of a [`SqlRowStream`] into chunks. Blobs are written as base64 strings, and
+/// non-finite floats as `null` in JSON.
+struct SqlExport {
+    rows: SqlRowStream,
+    format: SqlExportFormat,
+    /// The column names, already encoded as JSON object keys.
+    keys: Vec<String>,
+    buffer: Vec<u8>,
+    first: bool,
+    done: bool,
+}
+
+impl SqlExport {
+    fn new(rows: SqlRowStream, format: SqlExportFormat) -> Self {
+        let mut buffer = Vec::new();
+        match format {
+            SqlExportFormat::Ndjson => {}
+            SqlExportFormat::Csv => {
+                let fields = rows.columns.iter().map(|column| csv_field(column));
+                buffer.extend(fields.collect::<Vec<_>>().join(",").into_bytes());
+                buffer.extend(b"\r\n");
+            }
+            SqlExportFormat::Json => buffer.push(b'['),
+        }
+        let keys = rows
+            .columns
+            .iter()
+            .map(|column| json_string(column))
+            .collect();
+        Self {
+            rows,
+            format,
+            keys,
+            buffer,
+            first: true,
+            done: false,
+        }
+    }
+
+    fn write_row(&mut self, row: &SqlRow) {
+        let out = &mut self.buffer;
+        match self.format {
+            SqlExportFormat::Ndjson | SqlExportFormat::Json => {
+                if self.format == SqlExportFormat::Json && !self.first {
+                    out.push(b',');
+                }
+                out.push(b'{');
+                for (i, (key, value)) in self.keys.iter().zip(row.values()).enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    out.extend(key.as_bytes());
+                    out.push(b':');
+                    out.extend(json_value(value).into_bytes());
+                }
+                out.push(b'}');
+                if self.format == SqlExportFormat::Ndjson {
+                    out.push(b'\n');
+                }
+            }
+            SqlExportFormat::Csv => {
+                let fields = row.values().iter().map(csv_value).collect::<Vec<_>>();
+                out.extend(fields.join(",").into_bytes());
+                out.extend(b"\r\n");
+            }
+        }
+        self.first = false;
+    }
+}
+
+impl Stream for SqlExport {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while !this.done {
+            match this.rows.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(row))) => {
+                    this.write_row(&row);
+                    if this.buffer.len() >= CHUNK_SIZE || signal::is_near_cpu_limit() {
+                        return Poll::Ready(Some(Ok(std::mem::take(&mut this.buffer))));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => {
+                    this.done = true;
+                    if this.format == SqlExportFormat::Json {
+                        this.buffer.push(b']');
+                    }
+                }
+                // Send what is already encoded while the rows are paused.
+                Poll::Pending if this.buffer.is_empty() => return Poll::Pending,
+                Poll::Pending => return Poll::Ready(Some(Ok(std::mem::take(&mut this.buffer)))),
+            }
+        }
+        match this.buffer.is_empty() {
+            true => Poll::Ready(None),
+            false => Poll::Ready(Some(Ok(std::mem::take(&mut this.buffer)))),
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+fn json_value(value: &SqlStorageValue) -> String {
+    match value {
+        SqlStorageValue::Null => "null".to_string(),
+        SqlStorageValue::Boolean(b) => b.to_string(),
+        SqlStorageValue::Integer(i) => i.to_string(),
+        SqlStorageValue::Float(f) => serde_json::Value::from(*f).to_string(),
+        SqlStorageValue::String(s) => json_string(s),
+        SqlStorageValue::Blob(bytes) => json_string(&base64::encode(bytes)),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_value(value: &SqlStorageValue) -> String {
+    match value {
+        SqlStorageValue::Null => String::new(),
+        SqlStorageValue::Boolean(b) => b.to_string(),
+        SqlStorageValue::Integer(i) => i.to_string(),
+        SqlStorageValue::Float(f) => f.to_string(),
+        SqlStorageValue::String(s) => csv_field(s),
+        SqlStorageValue::Blob(bytes) => base64::encode(bytes),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::{FutureExt, TryStreamExt};
+
+    use super::*;
+
+    fn rows() -> SqlRowStream {
+        SqlRowStream::new(
+            vec!["id".to_string(), "note, \"quoted\"".to_string()].into(),
+            vec![
+                Ok(vec![
+                    SqlStorageValue::Integer(1),
+                    SqlStorageValue::String("a\nb".into()),
+                ]),
+                Ok(vec![SqlStorageValue::Float(1.5), SqlStorageValue::Null]),
+                Ok(vec![
+                    SqlStorageValue::Blob(vec![1, 2, 3]),
+                    SqlStorageValue::Float(f64::NAN),
+                ]),
+            ]
+            .into_iter(),
+        )
+    }
+
+    fn export(format: SqlExportFormat) -> String {
+        let chunks: Vec<Vec<u8>> = SqlExport::new(rows(), format)
+            .try_collect()
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        String::from_utf8(chunks.concat()).unwrap()
+    }
+
+    #[test]
+    fn exports_json() {
+        assert_eq!(
+            export(SqlExportFormat::Ndjson),
+            "{\"id\":1,\"note, \\\"quoted\\\"\":\"a\\nb\"}\n\
+             {\"id\":1.5,\"note, \\\"quoted\\\"\":null}\n\
+             {\"id\":\"AQID\",\"note, \\\"quoted\\\"\":null}\n"
+        );
+        assert_eq!(
+            export(SqlExportFormat::Json),
+            "[{\"id\":1,\"note, \\\"quoted\\\"\":\"a\\nb\"},\
+             {\"id\":1.5,\"note, \\\"quoted\\\"\":null},\
+             {\"id\":\"AQID\",\"note, \\\"quoted\\\"\":null}]"
+        );
+
+        let empty = SqlRowStream::new(Rc::from([]), std::iter::empty());
+        let chunks: Vec<Vec<u8>> = SqlExport::new(empty, SqlExportFormat::Json)
+            .try_collect()
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunks.concat(), b"[]");
+    }
+
+    #[test]
+    fn exports_csv() {
+        assert_eq!(
+            export(SqlExportFormat::Csv),
+            "id,\"note, \"\"quoted\"\"\"\r\n1,\"a\nb\"\r\n1.5,\r\nAQID,NaN\r\n"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    fmt::Write,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{FutureExt, Stream};
+use pin_project::pin_project;
+use serde::Serialize;
+
+use crate::{Delay, Error, Headers, Response, Result};
+
+/// A single [server-sent event](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+///
+/// ```no_run
+/// # use worker::*;
+/// # fn main() -> Result<()> {
+/// let event = sse::Event::new()
+///     .event("update")
+///     .id("42")
+///     .json_data(&serde_json::json!({ "count": 42 }))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the event's data. Data spanning several lines is sent as several `data` fields, which
+    /// the client joins back together.
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Set the event's data to `data` serialized as JSON.
+    pub fn json_data<T: Serialize>(self, data: &T) -> Result<Self> {
+        Ok(self.data(serde_json::to_string(data)?))
+    }
+
+    /// Set the event's name, which the client dispatches the event under. Events without a name
+    /// are dispatched as `message` events.
+    ///
+    /// # Panics
+    ///
+    /// If `event` contains a newline.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        let event = event.into();
+        assert_single_line("event", &event);
+        self.event = Some(event);
+        self
+    }
+
+    /// Set the event's ID, which the client sends back in the `Last-Event-ID` header when it
+    /// reconnects.
+    ///
+    /// # Panics
+    ///
+    /// If `id` contains a newline or a null character.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        let id = id.into();
+        assert_single_line("id", &id);
+        assert!(!id.contains('\0'), "SSE id cannot contain null characters");
+        self.id = Some(id);
+        self
+    }
+
+    /// Set how long the client waits before reconnecting when the connection is lost.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set a comment, which the client ignores.
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    fn encode(&self) -> String {
+        let mut buf = String::new();
+        if let Some(comment) = &self.comment {
+            for line in lines(comment) {
+                let _ = writeln!(buf, ":{line}");
+            }
+        }
+        if let Some(event) = &self.event {
+            let _ = writeln!(buf, "event: {event}");
+        }
+        if let Some(id) = &self.id {
+            let _ = writeln!(buf, "id: {id}");
+        }
+        if let Some(retry) = self.retry {
+            let _ = writeln!(buf, "retry: {}", retry.as_millis());
+        }
+        if let Some(data) = &self.data {
+            for line in lines(data) {
+                let _ = writeln!(buf, "data: {line}");
+            }
+        }
+        buf.push('\n');
+        buf
+    }
+}
+
+fn assert_single_line(field: &str, value: &str) {
+    assert!(
+        !value.contains(['\r', '\n']),
+        "SSE {field} cannot contain newlines"
+    );
+}
+
+/// Split `value` on any of the line terminators allowed in an event stream.
+fn lines(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .flat_map(|line| line.split('\r'))
+}
+
+/// Comments sent when no event has been sent for a while, so that the connection isn't closed by
+/// proxies as idle.
+#[derive(Debug, Clone)]
+pub struct KeepAlive {
+    interval: Duration,
+    text: String,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            text: String::new(),
+        }
+    }
+}
+
+impl KeepAlive {
+    /// Keep-alive comments sent after 15 seconds without events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long to wait after the last event before sending a keep-alive comment.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the text of the keep-alive comment.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+}
+
+/// A `text/event-stream` response body, built from a stream of [`Event`]s.
+///
+/// ```no_run
+/// use futures_util::stream;
+/// use worker::{sse::{Event, KeepAlive, Sse}, *};
+///
+/// fn events() -> Result<Response> {
+///     let events = stream::iter((0..3).map(|i| Ok::<_, Error>(Event::new().data(i.to_string()))));
+///     Sse::new(events).keep_alive(KeepAlive::new()).into_response()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S, E> Sse<S>
+where
+    S: Stream<Item = std::result::Result<Event, E>> + 'static,
+    E: Into<Error>,
+{
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: None,
+        }
+    }
+
+    /// Send keep-alive comments while the stream has no events.
+    pub fn keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// The encoded event stream.
+    pub fn into_stream(self) -> SseStream<S> {
+        SseStream {
+            delay: self
+                .keep_alive
+                .as_ref()
+                .map(|keep_alive| Box::pin(Delay::from(keep_alive.interval))),
+            keep_alive: self.keep_alive,
+            stream: self.stream,
+        }
+    }
+
+    fn headers() -> Result<Headers> {
+        let headers = Headers::new();
+        headers.set("Content-Type", "text/event-stream")?;
+        headers.set("Cache-Control", "no-cache")?;
+        Ok(headers)
+    }
+
+    /// Build a `200 OK` response streaming the events, with the `Content-Type` and
+    /// `Cache-Control` headers an event stream needs.
+    pub fn into_response(self) -> Result<Response> {
+        Ok(Response::from_stream(self.into_stream())?.with_headers(Self::headers()?))
+    }
+
+    /// Like [`into_response`](Sse::into_response), building an [`http::Response`].
+    #[cfg(feature = "http")]
+    pub fn into_http_response(self) -> Result<http::Response<crate::Body>> {
+        let body = crate::Body::from_stream(self.into_stream())?;
+        Ok(http::Response::builder()
+            .header(http::header::CONTENT_TYPE, "text/event-stream")
+            .header(http::header::CACHE_CONTROL, "no-cache")
+            .body(body)?)
+    }
+}
+
+/// The encoded stream of an [`Sse`] response, yielding one chunk per event.
+#[pin_project]
+#[derive(Debug)]
+pub struct SseStream<S> {
+    #[pin]
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+    delay: Option<Pin<Box<Delay>>>,
+}
+
+impl<S, E> Stream for SseStream<S>
+where
+    S: Stream<Item = std::result::Result<Event, E>>,
+    E: Into<Error>,
+{
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(event)) => {
+                if let (Some(keep_alive), Some(delay)) = (this.keep_alive.as_ref(), this.delay) {
+                    *delay = Box::pin(Delay::from(keep_alive.interval));
+                }
+                Poll::Ready(Some(
+                    event
+                        .map(|event| event.encode().into_bytes())
+                        .map_err(Into::into),
+                ))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => match (this.keep_alive.as_ref(), this.delay) {
+                (Some(keep_alive), Some(delay)) => {
+                    if delay.poll_unpin(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    *delay = Box::pin(Delay::from(keep_alive.interval));
+                    // Register the new timer with the waker.
+                    let _ = delay.poll_unpin(cx);
+                    let comment = Event::new().comment(keep_alive.text.as_str());
+                    Poll::Ready(Some(Ok(comment.encode().into_bytes())))
+                }
+                _ => Poll::Pending,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encodes_all_fields() {
+        let event = Event::new()
+            .comment("hello")
+            .event("update")
+            .id("7")
+            .retry(Duration::from_secs(3))
+            .data("first\nsecond\r\nthird\rfourth");
+        assert_eq!(
+            event.encode(),
+            ":hello\nevent: update\nid: 7\nretry: 3000\n\
+             data: first\ndata: second\ndata: third\ndata: fourth\n\n"
+        );
+
+        // The client strips one leading space, so a value's own leading space survives.
+        assert_eq!(Event::new().data(" x").encode(), "data:  x\n\n");
+    }
+
+    #[test]
+    fn encodes_json_data() {
+        let event = Event::new()
+            .json_data(&serde_json::json!({ "a": [1, 2] }))
+            .unwrap();
+        assert_eq!(event.encode(), "data: {\"a\":[1,2]}\n\n");
+    }
+
+    #[test]
+    fn encodes_empty_keep_alive_comment() {
+        assert_eq!(Event::new().comment("").encode(), ":\n\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "SSE event cannot contain newlines")]
+    fn rejects_multiline_event_names() {
+        let _ = Event::new().event("a\nb");
+    }
+}
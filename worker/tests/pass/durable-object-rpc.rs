@@ -0,0 +1,49 @@
+// `#[durable_object(rpc)]` exports the public `&self` methods of an `impl` block and generates a
+// typed stub calling them.
+use worker::*;
+
+#[durable_object]
+pub struct Counter {
+    state: State,
+}
+
+impl DurableObject for Counter {
+    fn new(state: State, _env: Env) -> Self {
+        Self { state }
+    }
+
+    async fn fetch(&self, _req: Request) -> Result<Response> {
+        Response::ok("ok")
+    }
+}
+
+#[durable_object(rpc)]
+impl Counter {
+    /// Add `amount` to the counter.
+    pub async fn add(&self, amount: u32, label: String) -> Result<u32> {
+        let _ = (&self.state, label);
+        Ok(amount)
+    }
+
+    pub fn read_all(&self) -> worker::Result<Vec<u32>> {
+        Ok(Vec::new())
+    }
+
+    // Private methods are not exported.
+    fn helper(&mut self) -> u32 {
+        0
+    }
+}
+
+async fn call(stub: Stub) -> Result<(u32, Vec<u32>)> {
+    let stub: CounterStub = stub.into();
+    let added = stub.add(1, "one".into()).await?;
+    let all = stub.read_all().await?;
+    let _: Stub = stub.into_inner();
+    Ok((added, all))
+}
+
+fn main() {
+    let _ = call;
+    let _ = Counter::helper;
+}
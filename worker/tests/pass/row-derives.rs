@@ -0,0 +1,53 @@
+// `#[derive(FromRow, ToParams)]` on named, tuple and unit structs, with `#[sql(..)]` attributes.
+use std::rc::Rc;
+
+use worker::{FromRow, SqlRow, SqlStorageValue, ToParams};
+
+#[derive(Debug, PartialEq, FromRow, ToParams)]
+struct User {
+    id: i64,
+    #[sql(rename = "display_name")]
+    name: String,
+    avatar: Option<Vec<u8>>,
+    #[sql(skip)]
+    cached: usize,
+}
+
+#[derive(Debug, PartialEq, FromRow, ToParams)]
+struct Pair(i64, #[sql(skip)] bool, String);
+
+#[derive(Debug, PartialEq, FromRow, ToParams)]
+struct Unit;
+
+fn row(columns: &[&str], values: Vec<SqlStorageValue>) -> SqlRow {
+    let columns: Rc<[String]> = columns.iter().map(|c| c.to_string()).collect();
+    SqlRow::new(columns, values)
+}
+
+fn main() {
+    let user = User::from_row(row(
+        &["display_name", "id", "avatar"],
+        vec!["Ada".into(), 7.into(), SqlStorageValue::Null],
+    ))
+    .unwrap();
+    assert_eq!(
+        user,
+        User {
+            id: 7,
+            name: "Ada".into(),
+            avatar: None,
+            cached: 0,
+        }
+    );
+    assert_eq!(
+        user.to_params(),
+        [7.into(), "Ada".into(), SqlStorageValue::Null]
+    );
+
+    let pair = Pair::from_row(row(&["a", "b"], vec![1.into(), "x".into()])).unwrap();
+    assert_eq!(pair, Pair(1, false, "x".into()));
+    assert_eq!(pair.to_params(), [1.into(), "x".into()]);
+
+    assert_eq!(Unit::from_row(row(&[], vec![])).unwrap(), Unit);
+    assert!(Unit.to_params().is_empty());
+}
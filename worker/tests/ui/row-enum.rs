@@ -0,0 +1,13 @@
+use worker::{FromRow, ToParams};
+
+#[derive(FromRow)]
+enum Status {
+    Active,
+}
+
+#[derive(ToParams)]
+enum Role {
+    Admin,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use worker::ToParams;
+
+#[derive(ToParams)]
+struct User {
+    #[sql(rename = 1)]
+    id: i64,
+}
+
+fn main() {}
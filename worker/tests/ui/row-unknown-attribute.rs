@@ -0,0 +1,9 @@
+use worker::FromRow;
+
+#[derive(FromRow)]
+struct User {
+    #[sql(default)]
+    id: i64,
+}
+
+fn main() {}
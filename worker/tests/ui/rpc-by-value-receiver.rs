@@ -0,0 +1,12 @@
+use worker::*;
+
+pub struct Counter;
+
+#[durable_object(rpc)]
+impl Counter {
+    pub async fn add(self, amount: u32) -> Result<u32> {
+        Ok(amount)
+    }
+}
+
+fn main() {}
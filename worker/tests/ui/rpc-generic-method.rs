@@ -0,0 +1,12 @@
+use worker::*;
+
+pub struct Counter;
+
+#[durable_object(rpc)]
+impl Counter {
+    pub async fn add<T>(&self, amount: T) -> Result<T> {
+        Ok(amount)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use worker::*;
+
+pub struct Counter;
+
+#[durable_object(rpc)]
+impl Counter {
+    pub async fn greet(&self, name: &str) -> Result<String> {
+        Ok(name.to_string())
+    }
+}
+
+fn main() {}
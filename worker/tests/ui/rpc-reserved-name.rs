@@ -0,0 +1,12 @@
+use worker::*;
+
+pub struct Counter;
+
+#[durable_object(rpc)]
+impl Counter {
+    pub async fn fetch(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use worker::*;
+
+pub struct Counter;
+
+#[durable_object(rpc)]
+impl Default for Counter {
+    fn default() -> Self {
+        Counter
+    }
+}
+
+fn main() {}